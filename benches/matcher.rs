@@ -0,0 +1,75 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+#[allow(dead_code)]
+#[path = "../src/matcher.rs"]
+mod matcher;
+
+use matcher::MatcherInput;
+
+const DIRS: &[&str] = &[
+    "home", "user", "projects", "luma", "src", "target", "debug", "documents", "photos", "music",
+];
+const EXTENSIONS: &[&str] = &["rs", "toml", "md", "png", "txt"];
+
+/// a synthetic index of 200k paths
+fn paths() -> Vec<String> {
+    (0..200_000usize)
+        .map(|i| {
+            format!(
+                "/{}/{}/{}/file_{i}.{}",
+                DIRS[i % 10],
+                DIRS[(i / 10) % 10],
+                DIRS[(i / 100) % 10],
+                EXTENSIONS[i % 5]
+            )
+        })
+        .collect()
+}
+
+fn bench_matcher(c: &mut Criterion) {
+    let paths = paths();
+    for query in ["luma rs", "projects src file.rs", "this-does-not-match-anything-at-all"] {
+        let input = MatcherInput::new(query.to_string(), false);
+        c.bench_function(&format!("matches 200k paths ({query})"), |b| {
+            b.iter(|| {
+                paths
+                    .iter()
+                    .filter(|path| input.matches(black_box(path)))
+                    .count()
+            });
+        });
+    }
+}
+
+/// the same sharding the file plugin uses for large indices
+fn bench_parallel_matcher(c: &mut Criterion) {
+    let paths = paths();
+    let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let input = MatcherInput::new("luma rs".to_string(), false);
+    c.bench_function(&format!("matches 200k paths on {threads} threads"), |b| {
+        b.iter(|| {
+            std::thread::scope(|scope| {
+                let handles = paths
+                    .chunks(paths.len().div_ceil(threads))
+                    .map(|shard| {
+                        let input = &input;
+                        scope.spawn(move || {
+                            shard
+                                .iter()
+                                .filter(|path| input.matches(black_box(path)))
+                                .count()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .sum::<usize>()
+            })
+        });
+    });
+}
+
+criterion_group!(benches, bench_matcher, bench_parallel_matcher);
+criterion_main!(benches);
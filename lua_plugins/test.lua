@@ -5,6 +5,8 @@ local function yield(value)
 end
 
 return {
+	description = "A test plugin",
+	display_name = "Test",
 	actions = {
 		luma.action.default("Default Action", ""),
 		luma.action.suggest("Suggest Action", ""),
@@ -11,7 +11,7 @@ use tokio::sync::{
     mpsc::{Sender, channel},
 };
 
-use crate::{Context, plugin::StringLike, sqlite::SqliteContext};
+use crate::{Context, config::Http, plugin::StringLike, sqlite::SqliteContext};
 
 pub struct Cache<K: Hash + Eq, V, E, F: FnMut(K) -> Result<(K, V), E>> {
     inner: HashMap<K, (V, Instant)>,
@@ -57,6 +57,39 @@ impl<K: Hash + Eq, V, E, F: FnMut(K) -> Result<(K, V), E>> Cache<K, V, E, F> {
         Ok(Some(&self.inner.entry(k).or_insert((v, Instant::now())).0))
     }
 
+    /// returns the cached value without fetching it if it's missing or expired
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let expires_after = self.expires_after;
+        self.inner
+            .get_mut(key)
+            .filter(|(_, cached_at)| cached_at.elapsed() < expires_after)
+            .map(|(value, _)| value)
+    }
+
+    /// returns if the value is cached and not expired
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner
+            .get(key)
+            .is_some_and(|(_, cached_at)| cached_at.elapsed() < self.expires_after)
+    }
+
+    /// removes the value, so it's fetched again the next time it's requested
+    pub fn invalidate<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.remove(key);
+    }
+
     pub fn clean(&mut self) {
         self.inner
             .retain(|_, v| Instant::now().duration_since(v.1) < self.expires_after);
@@ -73,6 +106,8 @@ pub struct HTTPResponse {
 pub struct HTTPCache {
     default_ttl: Duration,
     in_memory_cache_ttl: Duration,
+    default_timeout: Duration,
+    /// the responses and when they're removed from memory
     in_memory_cache: RwLock<HashMap<String, (Instant, Arc<HTTPResponse>)>>,
     waiting: RwLock<HashMap<String, Vec<Sender<Arc<HTTPResponse>>>>>,
     client: reqwest::Client,
@@ -83,6 +118,7 @@ impl HTTPCache {
         crate::sqlite::await_execute(&context, "CREATE TABLE get_request_cache(url TEXT, ttl INTEGER, body BLOB, err TEXT, result_code INTEGER)", [].into()).await?;
         Ok(())
     }
+    /// `timeout` and `ttl` override the durations of the config for this request
     pub async fn get(
         me: Arc<RwLock<HTTPCache>>,
         context: &SqliteContext,
@@ -102,7 +138,9 @@ impl HTTPCache {
         }
         let mut in_memory_cache = reader.in_memory_cache.write().await;
         if let Some(v) = in_memory_cache.get(url.to_str()) {
-            if v.1.ttl >= SystemTime::now() {
+            // entries are only kept in memory for a short while, even if the response is valid
+            // for longer, after which they're read from the database again
+            if v.0 > Instant::now() && v.1.ttl >= SystemTime::now() {
                 log::debug!("returning {url} from local cache");
                 return v.1.clone();
             }
@@ -200,7 +238,7 @@ impl HTTPCache {
         let res = match self
             .client
             .get(url)
-            .timeout(timeout.unwrap_or(Duration::from_secs(30)))
+            .timeout(timeout.unwrap_or(self.default_timeout))
             .send()
             .await
         {
@@ -234,14 +272,24 @@ impl HTTPCache {
         }
     }
 
-    pub fn new() -> Self {
-        HTTPCache {
-            default_ttl: Duration::from_secs(60 * 10),
-            in_memory_cache_ttl: Duration::from_secs(120),
+    pub fn new(config: &Http) -> Self {
+        let mut cache = HTTPCache {
+            default_ttl: Duration::ZERO,
+            in_memory_cache_ttl: Duration::ZERO,
+            default_timeout: Duration::ZERO,
             in_memory_cache: RwLock::default(),
             client: reqwest::Client::new(),
             waiting: <_>::default(),
-        }
+        };
+        cache.apply_config(config);
+        cache
+    }
+
+    /// applies the durations of the config to requests made from now on
+    pub fn apply_config(&mut self, config: &Http) {
+        self.default_ttl = Duration::from_secs(config.cache_ttl);
+        self.in_memory_cache_ttl = Duration::from_secs(config.memory_cache_ttl);
+        self.default_timeout = Duration::from_secs(config.timeout);
     }
 
     pub async fn clean(&self) {
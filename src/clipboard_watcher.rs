@@ -0,0 +1,190 @@
+use std::{
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// Notifies about new clipboard contents.
+///
+/// Watchers block, so they're run on their own thread.
+pub trait ClipboardWatcher: Send {
+    /// Blocks until the clipboard changes and returns the new text. Returns `None` once the
+    /// clipboard can't be watched anymore.
+    fn next(&mut self) -> Option<String>;
+}
+
+/// the watcher for the current session: the x11 one if an x server (or xwayland) is reachable,
+/// otherwise one polling `wl-paste`.
+pub fn watcher() -> Option<Box<dyn ClipboardWatcher>> {
+    #[cfg(unix)]
+    match x11::X11Watcher::new() {
+        Ok(v) => return Some(Box::new(v)),
+        Err(e) => log::debug!("failed to watch the x11 clipboard, polling instead: {e}"),
+    }
+    PollingWatcher::new().map(|v| Box::new(v) as Box<_>)
+}
+
+/// how often [`PollingWatcher`] reads the clipboard
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// reads the clipboard with `wl-paste` in an interval, for sessions without x11. copies that are
+/// replaced within the interval are missed.
+pub struct PollingWatcher {
+    last: Option<String>,
+}
+
+impl PollingWatcher {
+    fn new() -> Option<Self> {
+        if let Err(e) = Command::new("wl-paste").arg("--version").output() {
+            log::warn!("failed to watch the clipboard: wl-paste is not available ({e})");
+            return None;
+        }
+        // the current content isn't new
+        Some(Self { last: read() })
+    }
+}
+
+fn read() -> Option<String> {
+    let output = Command::new("wl-paste")
+        .args(["--no-newline", "--type", "text"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+impl ClipboardWatcher for PollingWatcher {
+    fn next(&mut self) -> Option<String> {
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let Some(text) = read() else { continue };
+            if self.last.as_ref() != Some(&text) {
+                self.last = Some(text.clone());
+                return Some(text);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod x11 {
+    use x11rb::{
+        COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, NONE,
+        connection::Connection,
+        errors::ReplyOrIdError,
+        protocol::{
+            Event,
+            xfixes::{ConnectionExt as _, SelectionEventMask},
+            xproto::{Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, Window, WindowClass},
+        },
+        rust_connection::RustConnection,
+    };
+
+    use super::ClipboardWatcher;
+
+    /// gets notified by the x server whenever the clipboard gets a new owner, using the xfixes
+    /// extension, and then asks the owner for the text.
+    pub struct X11Watcher {
+        conn: RustConnection,
+        window: Window,
+        clipboard: Atom,
+        utf8_string: Atom,
+        property: Atom,
+        incr: Atom,
+    }
+
+    impl X11Watcher {
+        pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+            let (conn, screen) = x11rb::connect(None)?;
+            let root = conn.setup().roots[screen].root;
+            // the extension has to be initialized before it can be used
+            conn.xfixes_query_version(5, 0)?.reply()?;
+            let window = conn.generate_id()?;
+            conn.create_window(
+                COPY_DEPTH_FROM_PARENT,
+                window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                COPY_FROM_PARENT,
+                &CreateWindowAux::new(),
+            )?;
+            let atom = |name: &[u8]| -> Result<Atom, ReplyOrIdError> {
+                Ok(conn.intern_atom(false, name)?.reply()?.atom)
+            };
+            let watcher = Self {
+                clipboard: atom(b"CLIPBOARD")?,
+                utf8_string: atom(b"UTF8_STRING")?,
+                property: atom(b"LUMA_CLIPBOARD")?,
+                incr: atom(b"INCR")?,
+                window,
+                conn,
+            };
+            watcher.conn.xfixes_select_selection_input(
+                window,
+                watcher.clipboard,
+                SelectionEventMask::SET_SELECTION_OWNER,
+            )?;
+            watcher.conn.flush()?;
+            Ok(watcher)
+        }
+
+        fn handle_event(&self, event: &Event) -> Result<Option<String>, ReplyOrIdError> {
+            match event {
+                Event::XfixesSelectionNotify(ev) if ev.owner != NONE => {
+                    self.conn.convert_selection(
+                        self.window,
+                        self.clipboard,
+                        self.utf8_string,
+                        self.property,
+                        ev.selection_timestamp,
+                    )?;
+                    self.conn.flush()?;
+                    Ok(None)
+                }
+                Event::SelectionNotify(ev) if ev.property != NONE => {
+                    let reply = self
+                        .conn
+                        .get_property(true, self.window, self.property, AtomEnum::ANY, 0, u32::MAX)?
+                        .reply()?;
+                    // large contents are sent in chunks, which isn't worth it for the history
+                    if reply.type_ == self.incr {
+                        log::debug!("ignoring a large clipboard content");
+                        return Ok(None);
+                    }
+                    Ok(Some(String::from_utf8_lossy(&reply.value).into_owned()))
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+
+    impl ClipboardWatcher for X11Watcher {
+        fn next(&mut self) -> Option<String> {
+            loop {
+                let event = match self.conn.wait_for_event() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        log::error!("stopped watching the clipboard: {e}");
+                        return None;
+                    }
+                };
+                match self.handle_event(&event) {
+                    Ok(Some(text)) => return Some(text),
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("stopped watching the clipboard: {e}");
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
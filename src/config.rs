@@ -1,6 +1,6 @@
 use std::{
     borrow::Borrow,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::{Debug, Display, Write},
     ops::Deref,
     path::{Path, PathBuf},
@@ -8,9 +8,11 @@ use std::{
     time::Duration,
 };
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table, Value};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
 pub struct ScanFilter {
     pub ignore_hidden: bool,
     pub deny_paths: Vec<ArcPath>,
@@ -74,22 +76,65 @@ fn def_false() -> bool {
     false
 }
 
+fn default_font_scale() -> f32 {
+    1.0
+}
+
+fn default_row_height() -> f32 {
+    DEFAULT_ROW_HEIGHT
+}
+
+fn default_popup_timeout() -> u64 {
+    10
+}
+
+fn default_web_search_url() -> String {
+    "https://duckduckgo.com/?q={}".into()
+}
+
+fn default_max_query_length() -> usize {
+    1024
+}
+
+fn default_log_max_size() -> u64 {
+    5 * 1024
+}
+
+fn default_log_rotations() -> u32 {
+    3
+}
+
+fn default_http_cache_ttl() -> u64 {
+    60 * 10
+}
+
+fn default_http_memory_cache_ttl() -> u64 {
+    120
+}
+
+fn default_http_timeout() -> u64 {
+    30
+}
+
 fn default_keybind() -> String {
     "Ctrl+Space".into()
 }
 
-fn none<T>() -> Option<T> {
-    None
+fn default_appimage_dirs() -> Vec<ArcPath> {
+    crate::utils::HOME_DIR
+        .iter()
+        .map(|home| home.join("Applications").as_path().into())
+        .collect()
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
 pub struct FileWatcherEntry {
     pub path: ArcPath,
     #[serde(default = "def_false")]
     pub watch: bool,
-    #[serde(default = "none")]
+    #[serde(default)]
     pub reindex_every: Option<Duration>,
-    #[serde(default = "<_>::default")]
+    #[serde(default)]
     pub filter: ScanFilter,
 }
 
@@ -106,6 +151,20 @@ impl<'de> Deserialize<'de> for ArcStr {
     }
 }
 
+impl JsonSchema for ArcStr {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(generator)
+    }
+}
+
 impl Serialize for ArcStr {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -134,6 +193,20 @@ impl<'de> Deserialize<'de> for ArcPath {
     }
 }
 
+impl JsonSchema for ArcPath {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        PathBuf::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        PathBuf::json_schema(generator)
+    }
+}
+
 impl Serialize for ArcPath {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -170,14 +243,60 @@ impl From<&str> for ArcStr {
     }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Files {
     pub entries: Vec<FileWatcherEntry>,
     #[serde(default = "def_false")]
     pub reindex_at_startup: bool,
+    /// shows paths in the home directory as `~/...`
+    #[serde(default = "def_true")]
+    pub abbreviate_home: bool,
+}
+
+impl Default for Files {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            reindex_at_startup: false,
+            abbreviate_home: true,
+        }
+    }
+}
+
+/// settings for the http requests of plugins, such as the exchange rates of fend
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct Http {
+    /// the seconds responses are cached for, unless the plugin asks for another duration
+    #[serde(default = "default_http_cache_ttl")]
+    pub cache_ttl: u64,
+    /// the seconds responses are kept in memory before they're read from the database again
+    #[serde(default = "default_http_memory_cache_ttl")]
+    pub memory_cache_ttl: u64,
+    /// the seconds after which requests time out, unless the plugin asks for another timeout
+    #[serde(default = "default_http_timeout")]
+    pub timeout: u64,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy)]
+impl Default for Http {
+    fn default() -> Self {
+        Self {
+            cache_ttl: default_http_cache_ttl(),
+            memory_cache_ttl: default_http_memory_cache_ttl(),
+            timeout: default_http_timeout(),
+        }
+    }
+}
+
+/// an entry that is shown when the search is empty
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct Favorite {
+    /// the prefix of the plugin the entry belongs to
+    pub plugin: String,
+    /// the name of the entry, as it is shown in the results
+    pub name: String,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum BlurAction {
     Refocus,
@@ -191,22 +310,461 @@ impl Display for BlurAction {
     }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+/// what enter does when nothing matches the query
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Fallback {
+    #[default]
+    None,
+    /// runs the query with `sh -c`. this has to be enabled explicitly, as anything typed into
+    /// the search is run.
+    Shell,
+    /// opens [`Config::web_search_url`] with the query
+    WebSearch,
+}
+
+/// a standard library lua plugins only get if they ask for it and it was granted
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LuaCapability {
+    /// the `io` library, to read and write files
+    Io,
+    /// the `os` library, for environment variables, dates and running commands
+    Os,
+}
+
+impl LuaCapability {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "io" => Some(Self::Io),
+            "os" => Some(Self::Os),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Io => "io",
+            Self::Os => "os",
+        }
+    }
+}
+
+/// the least severe log level that is shown to the user. info logs are sent as desktop
+/// notifications.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationLevel {
+    Error,
+    #[default]
+    Warn,
+    Info,
+}
+
+/// the least severe level that is written to the log
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    #[default]
+    Debug,
+    Trace,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Config {
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub files: Files,
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub on_blur: BlurAction,
     #[serde(default = "default_keybind")]
     pub keybind: String,
-    #[serde(default = "HashSet::new")]
+    /// a second hotkey that opens luma with the copied text as the query
+    #[serde(default)]
+    pub paste_keybind: Option<String>,
+    #[serde(default)]
     pub enabled_plugins: HashSet<String>,
     #[serde(default = "def_true")]
     pub auto_resize: bool,
-    #[serde(default = "Default::default", rename = "plugin")]
+    /// the length in bytes longer queries are cut off at
+    #[serde(default = "default_max_query_length")]
+    pub max_query_length: usize,
+    /// the factor all font sizes (and the search and action bar) are scaled by
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
+    /// the height of a single result entry
+    #[serde(default = "default_row_height")]
+    pub row_height: f32,
+    /// the height of the window when auto resize is disabled. updated when the window is
+    /// resized manually. defaults to the height of all entries.
+    #[serde(default)]
+    pub fixed_height: Option<f32>,
+    /// makes the `quit` command only hide the window, so luma keeps running in the background
+    /// and the hotkey keeps working. `kill` always exits.
+    #[serde(default = "def_true")]
+    pub resident: bool,
+    /// asks before running control actions that exit luma
+    #[serde(default = "def_true")]
+    pub confirm_destructive_actions: bool,
+    /// shows an icon in the system tray to open luma or its settings. changes apply after a
+    /// restart.
+    #[serde(default = "def_false")]
+    pub tray: bool,
+    /// installs an xdg autostart entry that starts luma in the background at login.
+    #[serde(default = "def_false")]
+    pub autostart: bool,
+    /// deprecated alias of `notification_level = "info"`, see [`Config::notification_level`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub notify_on_info: Option<bool>,
+    /// logs that are less severe than this are never shown outside of the log
+    #[serde(default)]
+    pub notification_level: NotificationLevel,
+    /// the least severe level that is written to the log. `RUST_LOG` takes precedence.
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// the log level of specific modules, e.g. `{ "luma::file_index" = "trace" }`
+    #[serde(default)]
+    pub log_modules: HashMap<String, LogLevel>,
+    /// the size in KiB after which the log file is moved to `latest.log.1` when luma starts or
+    /// the config changes. 0 lets it grow forever.
+    #[serde(default = "default_log_max_size")]
+    pub log_max_size: u64,
+    /// how many of the old log files (`latest.log.1`, `latest.log.2`, ...) are kept. older ones
+    /// are deleted.
+    #[serde(default = "default_log_rotations")]
+    pub log_rotations: u32,
+    /// shows warnings and errors as desktop notifications instead of popups
+    #[serde(default = "def_false")]
+    pub errors_as_notifications: bool,
+    /// the seconds after which warning popups close themselves, 0 keeps them open
+    #[serde(default = "default_popup_timeout")]
+    pub popup_timeout: u64,
+    /// the seconds after which error popups close themselves, 0 keeps them open
+    #[serde(default)]
+    pub error_popup_timeout: u64,
+    /// directories searched for desktop files in addition to the xdg ones
+    #[serde(default)]
+    pub extra_application_dirs: Vec<ArcPath>,
+    /// directories searched for executables in addition to the ones in $PATH
+    #[serde(default)]
+    pub extra_executable_dirs: Vec<ArcPath>,
+    /// moves the selection faster the longer an arrow key is held down
+    #[serde(default = "def_false")]
+    pub accelerate_navigation: bool,
+    /// shows the index of the selected result and the number of results in the search bar
+    #[serde(default = "def_true")]
+    pub show_counter: bool,
+    /// shows the version of luma in the action bar
+    #[serde(default = "def_true")]
+    pub show_version: bool,
+    /// keeps a history of the copied texts for plugins to show
+    #[serde(default = "def_false")]
+    pub watch_clipboard: bool,
+    /// keeps the prefix of a plugin in front of the search when the window is hidden, until it's
+    /// removed with backspace
+    #[serde(default = "def_false")]
+    pub sticky_prefix: bool,
+    /// what enter does when nothing matches the query
+    #[serde(default)]
+    pub fallback: Fallback,
+    /// the url searched with by the `web_search` fallback. `{}` is replaced with the query.
+    #[serde(default = "default_web_search_url")]
+    pub web_search_url: String,
+    /// groups the results by plugin, with a header above each group
+    #[serde(default = "def_false")]
+    pub group_results: bool,
+    /// directories the run plugin looks for appimages in
+    #[serde(default = "default_appimage_dirs")]
+    pub appimage_dirs: Vec<ArcPath>,
+    #[serde(default)]
+    pub http: Http,
+    /// entries that are shown in this order when the search is empty
+    #[serde(default)]
+    pub favorites: Vec<Favorite>,
+    /// keeps the cache database in write-ahead log mode, which makes writes faster. changes
+    /// apply after a restart.
+    #[serde(default = "def_false")]
+    pub sqlite_wal: bool,
+    /// the capabilities granted to lua plugins by their name, e.g. `{ bookmarks = ["io"] }`.
+    /// plugins only get the ones they ask for in their header. changes apply after a restart.
+    #[serde(default)]
+    pub lua_capabilities: HashMap<String, Vec<LuaCapability>>,
+    #[serde(default, rename = "plugin")]
     pub plugin_settings: PluginSettingsHolder,
 }
 
+const SEARCH_SIZE: f32 = 31.0;
+const ACTION_SIZE: f32 = 31.0;
+const ACTION_BAR_SIZE: f32 = 31.0;
+const HEADER_SIZE: f32 = 24.0;
+pub const DEFAULT_ROW_HEIGHT: f32 = 56.0;
+pub const DEFAULT_BASE_SIZE: f32 = SEARCH_SIZE + ACTION_BAR_SIZE;
+
+impl Config {
+    /// the notification level, with the deprecated `notify_on_info` applied. it used to decide
+    /// whether info logs were sent on their own, so `true` shows them and `false` hides them.
+    pub fn notification_level(&self) -> NotificationLevel {
+        match (self.notify_on_info, self.notification_level) {
+            (Some(true), _) => NotificationLevel::Info,
+            (Some(false), NotificationLevel::Info) => NotificationLevel::Warn,
+            (_, level) => level,
+        }
+    }
+
+    fn font_scale(&self) -> f32 {
+        self.font_scale.clamp(0.25, 4.0)
+    }
+
+    /// scales a font size by the configured font scale
+    pub fn text_size(&self, size: f32) -> f32 {
+        size * self.font_scale()
+    }
+
+    pub fn entry_size(&self) -> f32 {
+        self.row_height.max(1.0)
+    }
+
+    pub fn action_size(&self) -> f32 {
+        ACTION_SIZE * self.font_scale()
+    }
+
+    /// the height of the headers above groups of results
+    pub fn header_size(&self) -> f32 {
+        HEADER_SIZE * self.font_scale()
+    }
+
+    pub fn action_bar_size(&self) -> f32 {
+        ACTION_BAR_SIZE * self.font_scale()
+    }
+
+    /// the size of the window without any entries
+    pub fn base_size(&self) -> f32 {
+        SEARCH_SIZE * self.font_scale() + self.action_bar_size()
+    }
+
+    /// the maximum length of the search query. it's at most [`u16::MAX`], as the ranges of
+    /// [`StringLike`](crate::plugin::StringLike) can't index further.
+    pub fn max_query_length(&self) -> usize {
+        self.max_query_length.min(usize::from(u16::MAX))
+    }
+
+    /// the size of the window when auto resize is disabled
+    pub fn noresize_base_size(&self) -> f32 {
+        match self.fixed_height {
+            Some(height) => height.max(self.base_size()),
+            None => self.base_size() + crate::NUM_ENTRIES as f32 * self.entry_size(),
+        }
+    }
+
+    /// how long error or warning popups stay open, if they close themselves at all
+    pub fn popup_timeout(&self, error: bool) -> Option<Duration> {
+        let timeout = if error {
+            self.error_popup_timeout
+        } else {
+            self.popup_timeout
+        };
+        (timeout > 0).then_some(Duration::from_secs(timeout))
+    }
+
+    /// the directories desktop files are searched in
+    pub fn application_dirs(&self) -> impl Iterator<Item = &Path> {
+        crate::utils::APPLICATION_DIRS
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(self.extra_application_dirs.iter().map(|v| &**v))
+    }
+
+    /// the directories executables are searched in
+    pub fn executable_dirs(&self) -> impl Iterator<Item = &Path> {
+        crate::utils::EXECUTABLE_PATHS
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(self.extra_executable_dirs.iter().map(|v| &**v))
+    }
+
+    /// serializes the config as toml on top of `previous`, so the comments and formatting of
+    /// everything that didn't change are kept.
+    pub fn to_toml_keeping_format(&self, previous: &str) -> Result<String, toml::ser::Error> {
+        let serialized = toml::to_string_pretty(self)?;
+        let (Ok(mut document), Ok(new)) = (
+            previous.parse::<DocumentMut>(),
+            serialized.parse::<DocumentMut>(),
+        ) else {
+            return Ok(serialized);
+        };
+        merge_toml_table(document.as_table_mut(), new.into_table());
+        Ok(document.to_string())
+    }
+
+    /// the size of the window when showing `entries` entries with auto resize enabled
+    pub fn height_for(&self, entries: usize) -> f32 {
+        entries.min(crate::NUM_ENTRIES) as f32 * self.entry_size() + self.base_size()
+    }
+}
+
+/// updates `old` to contain the values of `new`, only touching the items that changed
+fn merge_toml_table(old: &mut Table, new: Table) {
+    let removed = old
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !new.contains_key(key))
+        .collect::<Vec<_>>();
+    for key in removed {
+        old.remove(&key);
+    }
+    for (key, new) in new {
+        let Some(old) = old.get_mut(&key) else {
+            old.insert(&key, new);
+            continue;
+        };
+        match (old, new) {
+            (Item::Table(old), Item::Table(new)) => merge_toml_table(old, new),
+            (Item::ArrayOfTables(old), Item::ArrayOfTables(new)) => {
+                let len = new.len();
+                for (i, new) in new.into_iter().enumerate() {
+                    match old.get_mut(i) {
+                        Some(old) => merge_toml_table(old, new),
+                        None => old.push(new),
+                    }
+                }
+                while old.len() > len {
+                    old.remove(len);
+                }
+            }
+            (Item::Value(old), Item::Value(mut new)) => {
+                if !same_toml_value(old, &new) {
+                    // keeps the comment behind the value
+                    *new.decor_mut() = old.decor().clone();
+                    *old = new;
+                }
+            }
+            (old, new) => *old = new,
+        }
+    }
+}
+
+fn same_toml_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => a.value().to_bits() == b.value().to_bits(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_toml_value(a, b))
+        }
+        (Value::InlineTable(a), Value::InlineTable(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_toml_value(a, b)))
+        }
+        _ => false,
+    }
+}
+
 use crate::plugin_settings::PluginSettingsHolder;
 #[allow(unused_imports)]
 pub use crate::plugin_settings::{PluginSettings, PluginSettingsValue};
+
+#[cfg(test)]
+mod test {
+    use toml_edit::DocumentMut;
+
+    use super::{Config, merge_toml_table};
+
+    /// merges `new` into `previous` the same way saving the config does
+    fn merge(previous: &str, new: &str) -> String {
+        let mut document = previous.parse::<DocumentMut>().unwrap();
+        let new = new.parse::<DocumentMut>().unwrap();
+        merge_toml_table(document.as_table_mut(), new.into_table());
+        document.to_string()
+    }
+
+    const PREVIOUS: &str = r#"# the theme
+theme = "dark" # or light
+keybind = "ctrl+space"
+
+[files]
+# indexed on startup
+index = true
+"#;
+
+    #[test]
+    fn keeps_comments_and_order() {
+        let new = "keybind = \"ctrl+space\"\ntheme = \"dark\"\n\n[files]\nindex = true\n";
+        assert_eq!(merge(PREVIOUS, new), PREVIOUS);
+    }
+
+    #[test]
+    fn changed_values() {
+        let new = "theme = \"light\"\nkeybind = \"alt+space\"\n\n[files]\nindex = false\n";
+        assert_eq!(
+            merge(PREVIOUS, new),
+            r#"# the theme
+theme = "light" # or light
+keybind = "alt+space"
+
+[files]
+# indexed on startup
+index = false
+"#
+        );
+    }
+
+    #[test]
+    fn removed_keys() {
+        let new = "theme = \"dark\"\n";
+        assert_eq!(
+            merge(PREVIOUS, new),
+            "# the theme\ntheme = \"dark\" # or light\n"
+        );
+    }
+
+    #[test]
+    fn arrays_of_tables() {
+        let previous = r#"[[dirs]]
+# home
+path = "~"
+
+[[dirs]]
+path = "/tmp"
+"#;
+        let grown =
+            "[[dirs]]\npath = \"~\"\n\n[[dirs]]\npath = \"/tmp\"\n\n[[dirs]]\npath = \"/opt\"\n";
+        assert_eq!(
+            merge(previous, grown),
+            r#"[[dirs]]
+# home
+path = "~"
+
+[[dirs]]
+path = "/tmp"
+
+[[dirs]]
+path = "/opt"
+"#
+        );
+        let shrunk = "[[dirs]]\npath = \"~\"\n";
+        assert_eq!(merge(previous, shrunk), "[[dirs]]\n# home\npath = \"~\"\n");
+    }
+
+    #[test]
+    fn unchanged() {
+        let value = toml::from_str::<toml::Value>(PREVIOUS).unwrap();
+        let serialized = toml::to_string_pretty(&value).unwrap();
+        assert_eq!(merge(PREVIOUS, &serialized), PREVIOUS);
+    }
+
+    #[test]
+    fn unchanged_config() {
+        let previous = "# my config\nkeybind = \"ctrl+space\" # toggles the window\n";
+        let config = toml::from_str::<Config>(previous).unwrap();
+        let saved = config.to_toml_keeping_format(previous).unwrap();
+        assert!(saved.starts_with("# my config\nkeybind = \"ctrl+space\" # toggles the window\n"));
+        assert_eq!(config.to_toml_keeping_format(&saved).unwrap(), saved);
+    }
+}
@@ -1,15 +1,20 @@
 use iced::Task;
 
 use crate::{
-    CustomData, Entry, Message, ResultBuilderRef, matcher::MatcherInput, plugin::StructPlugin,
-    special_windows::SpecialWindowState, utils,
+    CustomData, Entry, Message, ResultBuilderRef,
+    matcher::{MatchMode, MatcherInput},
+    plugin::{ALWAYS_ENABLED_PLUGINS, StringLike, StructPlugin},
+    special_windows::SpecialWindowState,
+    utils::{self, CONFIG_FILE},
 };
 
 #[derive(Clone, Copy)]
 pub enum Action {
     Quit,
+    Kill,
     Hide,
     ShowLogs,
+    OpenConfig,
     OpenSettings,
 }
 
@@ -17,18 +22,22 @@ impl Action {
     pub const fn get_name(self) -> &'static str {
         match self {
             Action::Quit => "quit",
+            Action::Kill => "kill",
             Action::Hide => "hide",
             Action::ShowLogs => "logs",
+            Action::OpenConfig => "config",
             Action::OpenSettings => "settings",
         }
     }
     pub const fn get_description(self) -> &'static str {
         match self {
-            Action::Quit => {
-                "Exit the runner (This will exit it entirely, not just hide the window)."
+            Action::Quit => "Hide the window, or exit the runner if `resident` is disabled",
+            Action::Kill => {
+                "Exit the runner (This will exit it entirely, the hotkey stops working)."
             }
             Action::Hide => "Hides the window",
             Action::ShowLogs => "Open the latest application logs",
+            Action::OpenConfig => "Open the config file in your editor",
             Action::OpenSettings => "Open the settings",
         }
     }
@@ -36,11 +45,31 @@ impl Action {
 
 static ACTIONS: &[Action] = &[
     Action::Quit,
+    Action::Kill,
     Action::Hide,
     Action::ShowLogs,
+    Action::OpenConfig,
     Action::OpenSettings,
 ];
 
+#[derive(Clone)]
+enum Command {
+    Action(Action),
+    SetPluginEnabled(StringLike, bool),
+}
+
+/// returns if the input looks like it's trying to enable or disable a plugin. without the prefix
+/// the full word is required, so short queries like `d` don't list every plugin.
+fn is_toggle_query(input: &MatcherInput) -> bool {
+    input.words().first().is_some_and(|word| {
+        if input.has_prefix() {
+            "enable".starts_with(&**word) || "disable".starts_with(&**word)
+        } else {
+            word == "enable" || word == "disable"
+        }
+    })
+}
+
 #[derive(Default)]
 pub struct ControlPlugin;
 
@@ -49,22 +78,52 @@ impl StructPlugin for ControlPlugin {
         "control"
     }
 
+    fn description() -> &'static str {
+        "Control luma itself (quit, settings, logs, enabling plugins)"
+    }
+
     async fn get_for_values(
         &self,
         input: &MatcherInput,
         builder: ResultBuilderRef<'_>,
-        _: crate::PluginContext<'_>,
+        ctx: crate::PluginContext<'_>,
     ) {
         let iter = ACTIONS
             .iter()
-            .filter(|&action| input.matches(action.get_name()))
+            .filter(|&action| input.matches_with(action.get_name(), MatchMode::Prefix))
             .map(|action| {
                 Entry::new(
                     action.get_name(),
                     action.get_description(),
-                    CustomData::new(*action),
+                    CustomData::new(Command::Action(*action)),
                 )
             });
+        if !builder.commit(iter).await || !is_toggle_query(input) {
+            return;
+        }
+        let iter = ctx
+            .plugins()
+            .filter(|plugin| !ALWAYS_ENABLED_PLUGINS.contains(&plugin.prefix.to_str()))
+            .filter_map(|plugin| {
+                let name = if plugin.enabled {
+                    format!("disable {}", plugin.prefix)
+                } else {
+                    format!("enable {}", plugin.prefix)
+                };
+                if !input.matches_with(&name, MatchMode::Prefix) {
+                    return None;
+                }
+                let subtitle = if plugin.enabled {
+                    "Currently enabled"
+                } else {
+                    "Currently disabled"
+                };
+                Some(Entry::new(
+                    name,
+                    subtitle,
+                    CustomData::new(Command::SetPluginEnabled(plugin.prefix, !plugin.enabled)),
+                ))
+            });
         builder.commit(iter).await;
     }
 
@@ -76,16 +135,49 @@ impl StructPlugin for ControlPlugin {
         _: &str,
         ctx: crate::PluginContext<'_>,
     ) -> iced::Task<Message> {
-        match thing.into::<Action>() {
-            Action::Quit => Task::done(Message::Exit),
-            Action::Hide => Task::none(),
-            Action::ShowLogs => {
+        match thing.into::<Command>() {
+            // the window is hidden after every action, so only exiting needs to be handled
+            Command::Action(Action::Quit) if ctx.global_config.resident => Task::none(),
+            Command::Action(action @ (Action::Quit | Action::Kill))
+                if ctx.global_config.confirm_destructive_actions =>
+            {
+                Task::done(Message::OpenSpecial(SpecialWindowState::new_confirm_popup(
+                    format!("Do you really want to {}? Luma will exit.", action.get_name()),
+                    Message::Exit,
+                )))
+            }
+            Command::Action(Action::Quit | Action::Kill) => Task::done(Message::Exit),
+            Command::Action(Action::Hide) => Task::none(),
+            Command::Action(Action::ShowLogs) => {
                 utils::open_file(&**crate::logging::LOG_FILE);
                 Task::none()
             }
-            Action::OpenSettings => Task::done(Message::OpenSpecial(SpecialWindowState::settings(
-                Clone::clone(&*ctx.global_config),
-            ))),
+            Command::Action(Action::OpenConfig) => {
+                if !CONFIG_FILE.exists() {
+                    if let Some(parent) = CONFIG_FILE.parent() {
+                        _ = std::fs::create_dir_all(parent);
+                    }
+                    if let Err(e) = std::fs::write(&*CONFIG_FILE, crate::DEFAULT_CONFIG) {
+                        log::error!("failed to create {}: {e}", CONFIG_FILE.display());
+                        return Task::none();
+                    }
+                }
+                utils::open_file(CONFIG_FILE.as_path());
+                Task::none()
+            }
+            Command::Action(Action::OpenSettings) => Task::done(Message::OpenSpecial(
+                SpecialWindowState::settings(Clone::clone(&*ctx.global_config)),
+            )),
+            // the plugins get reinitialized the next time the window is shown.
+            Command::SetPluginEnabled(plugin, enabled) => {
+                let mut config = Clone::clone(&*ctx.global_config);
+                if enabled {
+                    config.enabled_plugins.insert(plugin.into());
+                } else {
+                    config.enabled_plugins.remove(plugin.to_str());
+                }
+                Task::done(Message::UpdateConfig(config.into(), true))
+            }
         }
     }
 
@@ -0,0 +1,276 @@
+// Plugin to calculate dates, e.g. `+3 weeks`, `2025-12-25 - 2 days` or `days until 2025-12-25`
+
+use chrono::{Local, Months, NaiveDate, TimeDelta};
+use iced::{Task, clipboard};
+
+use crate::{
+    Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef, StructPlugin,
+    matcher::MatcherInput,
+};
+
+#[derive(Default)]
+pub struct DatePlugin;
+
+/// returns if the query could be a date expression, so other queries aren't parsed at all
+fn looks_like_date(query: &str) -> bool {
+    query.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-')
+        || query.split_whitespace().next().is_some_and(|word| {
+            matches!(
+                word,
+                "today" | "now" | "tomorrow" | "yesterday" | "in" | "days"
+            )
+        })
+}
+
+/// parses the date an expression starts with, if any
+fn parse_base(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" | "now" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        "yesterday" => today.pred_opt(),
+        _ => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+    }
+}
+
+fn add(date: NaiveDate, amount: i64, unit: &str) -> Option<NaiveDate> {
+    let months = match unit {
+        "d" | "day" | "days" => return date.checked_add_signed(TimeDelta::try_days(amount)?),
+        "w" | "week" | "weeks" => {
+            return date.checked_add_signed(TimeDelta::try_weeks(amount)?);
+        }
+        "m" | "month" | "months" => amount,
+        "y" | "year" | "years" => amount.checked_mul(12)?,
+        _ => return None,
+    };
+    let abs = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months < 0 {
+        date.checked_sub_months(abs)
+    } else {
+        date.checked_add_months(abs)
+    }
+}
+
+/// parses a single offset such as `+3 weeks`, `- 2d`, `in 4 days` or `1 year ago`, returning the
+/// amount, the unit and the remaining words.
+fn parse_offset<'a>(mut words: &'a [&'a str]) -> Option<(i64, &'a str, &'a [&'a str])> {
+    if let ["in", rest @ ..] = words {
+        words = rest;
+    }
+    let (negative, words) = match words {
+        ["+", rest @ ..] => (false, rest),
+        ["-", rest @ ..] => (true, rest),
+        _ => (false, words),
+    };
+    let [amount, rest @ ..] = words else {
+        return None;
+    };
+    // the unit can be written right after the amount, as in `3d`
+    let unit_start = amount
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(amount.len());
+    let (amount, unit) = amount.split_at(unit_start);
+    let mut amount = amount.parse::<i64>().ok()?;
+    let (unit, mut rest) = match (unit, rest) {
+        ("", [unit, rest @ ..]) => (*unit, rest),
+        ("", []) => return None,
+        (unit, rest) => (unit, rest),
+    };
+    if let ["ago", remaining @ ..] = rest {
+        amount = -amount;
+        rest = remaining;
+    }
+    if negative {
+        amount = -amount;
+    }
+    Some((amount, unit, rest))
+}
+
+/// parses a date optionally followed by any amount of offsets. the date defaults to today.
+fn parse_date(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let (first, after_first) = words.split_first()?;
+    let (mut date, mut rest) = match parse_base(first, today) {
+        Some(date) => (date, after_first),
+        None => (today, words),
+    };
+    while !rest.is_empty() {
+        let (amount, unit, remaining) = parse_offset(rest)?;
+        date = add(date, amount, unit)?;
+        rest = remaining;
+    }
+    Some(date)
+}
+
+fn date_entry(date: NaiveDate) -> Entry {
+    let name = date.format("%Y-%m-%d").to_string();
+    Entry::new(
+        name.clone(),
+        date.format("%A, %-d %B %Y").to_string(),
+        CustomData::new(name),
+    )
+}
+
+/// handles `days until <date>` and `days since <date>`
+fn days_entry(words: &[&str], today: NaiveDate) -> Option<Entry> {
+    let (until, words) = match words {
+        ["days", "until" | "till" | "to", rest @ ..] => (true, rest),
+        ["days", "since" | "from", rest @ ..] => (false, rest),
+        _ => return None,
+    };
+    let date = parse_date(words, today)?;
+    let days = if until {
+        (date - today).num_days()
+    } else {
+        (today - date).num_days()
+    };
+    let name = if days.abs() == 1 {
+        format!("{days} day")
+    } else {
+        format!("{days} days")
+    };
+    let subtitle = format!(
+        "{} {}",
+        if until { "until" } else { "since" },
+        date.format("%A, %-d %B %Y")
+    );
+    Some(Entry::new(name, subtitle, CustomData::new(days.to_string())))
+}
+
+impl StructPlugin for DatePlugin {
+    fn prefix() -> &'static str {
+        "date"
+    }
+
+    fn description() -> &'static str {
+        "Calculate dates, e.g. `+3 weeks` or `days until 2025-12-25`"
+    }
+
+    async fn get_for_values(
+        &self,
+        input: &MatcherInput,
+        builder: ResultBuilderRef<'_>,
+        _: PluginContext<'_>,
+    ) {
+        let query = input.input().to_lowercase();
+        if !input.has_prefix() && !looks_like_date(&query) {
+            return;
+        }
+        let words = query.split_whitespace().collect::<Vec<_>>();
+        let today = Local::now().date_naive();
+        let entry = if words.first() == Some(&"days") {
+            days_entry(&words, today)
+        } else {
+            parse_date(&words, today).map(date_entry)
+        };
+        // invalid expressions just don't produce a result
+        if let Some(entry) = entry {
+            builder.add(entry.perfect(input.has_prefix())).await;
+        }
+    }
+
+    async fn init(&mut self, _: PluginContext<'_>) {}
+
+    fn handle_pre(&self, thing: CustomData, _: &str, _: PluginContext<'_>) -> Task<Message> {
+        clipboard::write(thing.into::<String>())
+    }
+
+    fn actions(&self) -> &'static [Action] {
+        const { &[Action::default("Copy to clipboard", "")] }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::{add, days_entry, parse_date, parse_offset};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn words(s: &str) -> Vec<&str> {
+        s.split_whitespace().collect()
+    }
+
+    #[test]
+    fn offsets() {
+        let cases: &[(&str, Option<(i64, &str, usize)>)] = &[
+            ("+3 weeks", Some((3, "weeks", 0))),
+            ("- 2d", Some((-2, "d", 0))),
+            ("in 4 days", Some((4, "days", 0))),
+            ("1 year ago", Some((-1, "year", 0))),
+            ("3d + 1 w", Some((3, "d", 3))),
+            ("+ 3", None),
+            ("+ three days", None),
+        ];
+        for (input, expected) in cases {
+            let words = words(input);
+            let result =
+                parse_offset(&words).map(|(amount, unit, rest)| (amount, unit, rest.len()));
+            assert_eq!(result, *expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn units() {
+        let today = date("2025-01-31");
+        let cases = [
+            (1, "d", Some("2025-02-01")),
+            (2, "days", Some("2025-02-02")),
+            (-1, "week", Some("2025-01-24")),
+            (1, "m", Some("2025-02-28")),
+            (-2, "months", Some("2024-11-30")),
+            (1, "years", Some("2026-01-31")),
+            (5, "ms", None),
+            (5, "s", None),
+            (5, "daysss", None),
+        ];
+        for (amount, unit, expected) in cases {
+            assert_eq!(
+                add(today, amount, unit),
+                expected.map(date),
+                "{amount} {unit}"
+            );
+        }
+    }
+
+    #[test]
+    fn dates() {
+        let today = date("2025-06-15");
+        let cases = [
+            ("today", Some("2025-06-15")),
+            ("tomorrow", Some("2025-06-16")),
+            ("yesterday - 1 day", Some("2025-06-13")),
+            ("+3 weeks", Some("2025-07-06")),
+            ("2025-12-25 - 2 days", Some("2025-12-23")),
+            ("in 1 month 2 days", Some("2025-07-17")),
+            ("2 years ago", Some("2023-06-15")),
+            ("5ms", None),
+            ("hello", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_date(&words(input), today),
+                expected.map(date),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn days() {
+        let today = date("2025-06-15");
+        let cases = [
+            ("days until 2025-06-16", Some("1 day")),
+            ("days till 2025-12-25", Some("193 days")),
+            ("days since 2025-06-01", Some("14 days")),
+            ("days from tomorrow", Some("-1 day")),
+            ("days until", None),
+            ("days between 2025-06-16", None),
+        ];
+        for (input, expected) in cases {
+            let name = days_entry(&words(input), today).map(|entry| entry.name.to_string());
+            assert_eq!(name.as_deref(), expected, "{input}");
+        }
+    }
+}
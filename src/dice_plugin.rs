@@ -1,5 +1,5 @@
 use iced::clipboard;
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use std::fmt::Write;
 
 use crate::{
@@ -15,6 +15,10 @@ impl StructPlugin for DicePlugin {
         "roll"
     }
 
+    fn description() -> &'static str {
+        "Roll dice, e.g. `2d6 1d20`, or `2d6 seed=42` for reproducible rolls"
+    }
+
     async fn get_for_values(
         &self,
         input: &MatcherInput,
@@ -25,22 +29,15 @@ impl StructPlugin for DicePlugin {
         if words.is_empty() {
             return;
         }
-        let mut entries = Vec::with_capacity(words.len());
-        let mut total = 0;
-        for entry in words.iter().map(|v| v as &str).filter_map(roll) {
-            entries.push(entry.0);
-            total += entry.1;
-        }
-        if entries.len() > 1 {
-            entries.insert(
-                0,
-                Entry::new(
-                    format!("Overall Total:  {total}"),
-                    StringLike::Empty,
-                    CustomData::new(total),
-                ),
-            );
-        }
+        // `=` splits words, so `seed=42` is the word `seed` followed by the seed
+        let seed = words
+            .windows(2)
+            .find_map(|v| (v[0] == "seed").then(|| v[1].parse::<u64>().ok())?);
+        // the thread rng can't be held across the await below, so rolling happens before it
+        let entries = match seed {
+            Some(seed) => roll_all(words, &mut StdRng::seed_from_u64(seed)),
+            None => roll_all(words, &mut rand::rng()),
+        };
         builder.commit(entries.into_iter()).await;
     }
 
@@ -60,7 +57,39 @@ impl StructPlugin for DicePlugin {
     }
 }
 
-fn roll(s: &str) -> Option<(Entry, usize)> {
+/// rolls every word that is a dice, with an overall total in front if there's more than one
+fn roll_all(words: &[String], rng: &mut impl Rng) -> Vec<Entry> {
+    let mut entries = Vec::with_capacity(words.len());
+    let mut rolls = Vec::new();
+    for (entry, results) in words.iter().filter_map(|v| roll(v, rng)) {
+        entries.push(entry);
+        rolls.extend(results);
+    }
+    if entries.len() > 1 {
+        let total = rolls.iter().sum::<usize>();
+        entries.insert(
+            0,
+            Entry::new(
+                format!("Overall Total:  {total}"),
+                stats(&rolls).map_or(StringLike::Empty, StringLike::from),
+                CustomData::new(total),
+            ),
+        );
+    }
+    entries
+}
+
+/// the min, max and average of the individual dice
+#[allow(clippy::cast_precision_loss)]
+fn stats(rolls: &[usize]) -> Option<String> {
+    let min = rolls.iter().min()?;
+    let max = rolls.iter().max()?;
+    let average = rolls.iter().sum::<usize>() as f64 / rolls.len() as f64;
+    Some(format!("Min: {min}  Max: {max}  Average: {average:.2}"))
+}
+
+/// returns the entry and the result of every die
+fn roll(s: &str, rng: &mut impl Rng) -> Option<(Entry, Vec<usize>)> {
     let (dice, sides) = s.split_once('d')?;
     let dice: usize = dice.trim().parse().ok()?;
     let sides: usize = sides.trim().parse().ok()?;
@@ -69,8 +98,8 @@ fn roll(s: &str) -> Option<(Entry, usize)> {
     }
 
     let mut result = 0usize;
+    let mut rolls = Vec::with_capacity(dice);
     let mut subtitle = String::from("Rolls:");
-    let mut rng = rand::rng();
 
     for i in 0..dice {
         let res = rng.random_range(1..=sides);
@@ -79,9 +108,53 @@ fn roll(s: &str) -> Option<(Entry, usize)> {
         }
         subtitle.push(' ');
         result += res;
+        rolls.push(res);
         _ = write!(subtitle, "{res}");
     }
 
     let name = format!("Rolled {dice}d{sides} - Total: {result}");
-    Some((Entry::new(name, subtitle, CustomData::new(result)), result))
+    Some((Entry::new(name, subtitle, CustomData::new(result)), rolls))
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{DicePlugin, roll, stats};
+    use crate::{Context, filter_service::collect_entries};
+
+    #[test]
+    fn rolls_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let (_, rolls) = roll("3d6", &mut rng).unwrap();
+            assert_eq!(rolls.len(), 3);
+            assert!(rolls.iter().all(|v| (1..=6).contains(v)), "{rolls:?}");
+        }
+        assert!(roll("1d0", &mut rng).is_none());
+        assert!(roll("d6", &mut rng).is_none());
+    }
+
+    #[test]
+    fn statistics() {
+        assert_eq!(
+            stats(&[1, 6, 2]).as_deref(),
+            Some("Min: 1  Max: 6  Average: 3.00")
+        );
+        assert_eq!(stats(&[]), None);
+    }
+
+    #[test]
+    fn seeded_rolls_are_reproducible() {
+        let context = Context::test();
+        let names = |query| {
+            collect_entries(&DicePlugin, query, true, &context)
+                .into_iter()
+                .map(|v| v.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let rolls = names("2d6 1d20 seed=42");
+        assert_eq!(rolls.len(), 3);
+        assert_eq!(rolls, names("2d6 1d20 seed=42"));
+    }
 }
@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     collections::HashMap,
     sync::{
         Arc, LazyLock,
@@ -8,18 +9,101 @@ use std::{
 };
 
 use fend_core::{Context, Interrupt};
-use iced::{Task, clipboard};
+use iced::{
+    Task,
+    advanced::graphics::core::SmolStr,
+    clipboard,
+    keyboard::{Key, Modifiers, key::Named},
+};
 use serde::Deserialize;
 use tokio::sync::RwLock;
 
 use crate::{
-    Action, CustomData, Entry, Message, StructPlugin, cache::HTTPCache,
-    filter_service::ResultBuilderRef, matcher::MatcherInput, utils,
+    Action, CustomData, Entry, Message, PluginContext, StructPlugin, cache::HTTPCache,
+    config::PluginSettings, filter_service::ResultBuilderRef, matcher::MatcherInput,
+    plugin::StringLike, sqlite, utils,
 };
 
 #[derive(Default)]
 pub struct FendPlugin(RwLock<Context>);
 
+const ATTRIBUTION: &str = "exchange rates by exchangerate-api.com • powered by fend";
+
+#[derive(Clone)]
+struct FendResult {
+    /// the input that produced this result
+    input: Arc<str>,
+    /// the main result as displayed
+    value: Arc<str>,
+    /// the exact form of the result, falls back to `value` if fend can't produce one.
+    exact: Arc<str>,
+}
+
+/// a function or constant suggested while typing its name
+#[derive(Clone)]
+struct FendCompletion {
+    /// the whole query with the identifier completed
+    query: Arc<str>,
+}
+
+const COMPLETION_ACTIONS: &[Action] = &[Action::suggest("Complete", "complete")];
+
+/// the functions and constants of fend, which aren't exposed by `fend_core`
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("abs", "absolute value"),
+    ("acos", "inverse cosine"),
+    ("acosh", "inverse hyperbolic cosine"),
+    ("arg", "argument of a complex number"),
+    ("asin", "inverse sine"),
+    ("asinh", "inverse hyperbolic sine"),
+    ("atan", "inverse tangent"),
+    ("atanh", "inverse hyperbolic tangent"),
+    ("average", "mean of a distribution"),
+    ("base", "converts to the given base"),
+    ("binary", "converts to base 2"),
+    ("cbrt", "cube root"),
+    ("ceil", "rounds up"),
+    ("cis", "cos x + i sin x"),
+    ("conjugate", "complex conjugate"),
+    ("cos", "cosine"),
+    ("cosh", "hyperbolic cosine"),
+    ("cubic", "x^3"),
+    ("decimal", "converts to base 10"),
+    ("e", "euler's number"),
+    ("exact", "shows the exact value"),
+    ("exp", "e^x"),
+    ("false", "boolean false"),
+    ("fibonacci", "nth fibonacci number"),
+    ("float", "shows the value as a decimal"),
+    ("floor", "rounds down"),
+    ("fraction", "shows the value as a fraction"),
+    ("hexadecimal", "converts to base 16"),
+    ("imag", "imaginary part"),
+    ("ln", "natural logarithm"),
+    ("log", "base 10 logarithm"),
+    ("log10", "base 10 logarithm"),
+    ("log2", "base 2 logarithm"),
+    ("mean", "mean of a distribution"),
+    ("mixed_fraction", "shows the value as a mixed fraction"),
+    ("not", "boolean not"),
+    ("octal", "converts to base 8"),
+    ("phi", "golden ratio"),
+    ("pi", "ratio of a circle's circumference to its diameter"),
+    ("real", "real part"),
+    ("roll", "rolls a die, e.g. roll d20"),
+    ("round", "rounds to the nearest integer"),
+    ("sample", "random sample of a distribution"),
+    ("sin", "sine"),
+    ("sinh", "hyperbolic sine"),
+    ("sqrt", "square root"),
+    ("square", "x^2"),
+    ("tan", "tangent"),
+    ("tanh", "hyperbolic tangent"),
+    ("tau", "2 pi"),
+    ("today", "the current date"),
+    ("true", "boolean true"),
+];
+
 // TODO: currency handler
 
 impl Interrupt for ResultBuilderRef<'_> {
@@ -54,41 +138,162 @@ impl StructPlugin for FendPlugin {
         const {
             &[
                 Action::default("Copy Value", "copy"),
+                Action::new(
+                    "Copy Exact Value",
+                    "copy_exact",
+                    (Modifiers::CTRL, Key::Named(Named::Enter)),
+                ),
                 Action::suggest("Suggest Value", "suggest").keep_open(),
+                Action::new(
+                    "Run Again",
+                    "rerun",
+                    (Modifiers::CTRL, Key::Character(SmolStr::new_inline("r"))),
+                )
+                .keep_open(),
                 Action::without_shortcut("About Fend", "fend").keep_open(),
                 Action::without_shortcut("About Exchangerate API", "exchangerate").keep_open(),
             ]
         }
     }
 
+    fn actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        if data.get::<FendCompletion>().is_some() {
+            Cow::Borrowed(COMPLETION_ACTIONS)
+        } else {
+            Cow::Borrowed(self.actions())
+        }
+    }
+
+    fn completions(&self, query: &str, _: PluginContext<'_>) -> Vec<String> {
+        let Some(partial) = partial_identifier(query) else {
+            return Vec::new();
+        };
+        let before = &query[..query.len() - partial.len()];
+        IDENTIFIERS
+            .iter()
+            .filter(|(name, _)| name.starts_with(partial))
+            .map(|(name, _)| format!("{before}{name}"))
+            .collect()
+    }
+
     fn prefix() -> &'static str {
         "fend"
     }
 
+    fn description() -> &'static str {
+        "Calculations and unit conversions"
+    }
+
+    fn config() -> Option<PluginSettings> {
+        let mut values = HashMap::new();
+        values.insert(
+            "prioritize_results".into(),
+            PluginSettings::Toggle {
+                label: Some("Show results above the results of other plugins".into()),
+                default: true,
+            },
+        );
+        values.insert(
+            "enable_currency".into(),
+            PluginSettings::Toggle {
+                label: Some("Convert currencies, which fetches the exchange rates online".into()),
+                default: true,
+            },
+        );
+        values.insert(
+            "history_length".into(),
+            PluginSettings::IntInput {
+                min: Some(0),
+                max: None,
+                step: 1,
+                default: 100,
+                label: Some("Amount of calculations kept in the history".into()),
+            },
+        );
+        Some(PluginSettings::Object {
+            values,
+            label: Some("fend".into()),
+        })
+    }
+
     async fn get_for_values(
         &self,
         input: &MatcherInput,
         builder: ResultBuilderRef<'_>,
-        _: crate::PluginContext<'_>,
+        context: crate::PluginContext<'_>,
     ) {
-        // for some reason rust doesn't like this block not being here :< [it thinks the writer is
-        // being dropped after the await, even tho it gets moved into the drop function?]
-        let Ok(result) =
-            fend_core::evaluate_with_interrupt(input.input(), &mut *self.0.write().await, &builder)
-        else {
+        if input.has_prefix()
+            && let Some(query) = input.input().trim_start().strip_prefix("history")
+        {
+            return show_history(query, builder, &context).await;
+        }
+        if is_trivial_input(input) {
             return;
+        }
+        if input.has_prefix() {
+            show_completions(input.input(), builder).await;
+        }
+        // the writer has to be dropped in a block, otherwise rust thinks it's held across the
+        // await below and the future is no longer `Send`.
+        let (result, exact) = {
+            let mut writer = self.0.write().await;
+            let Ok(result) =
+                fend_core::evaluate_with_interrupt(input.input(), &mut writer, &builder)
+            else {
+                return;
+            };
+            let result = result.get_main_result().trim().to_string();
+            // fend marks inexact results, exact ones don't need to be evaluated a second time
+            if result.starts_with("approx. ") {
+                let exact = fend_core::evaluate_with_interrupt(
+                    &format!("{} to exact", input.input()),
+                    &mut writer,
+                    &builder,
+                )
+                .ok()
+                .map(|v| v.get_main_result().trim().to_string())
+                .filter(|v| !v.is_empty());
+                (result, exact)
+            } else {
+                (result, None)
+            }
         };
-        let result = result.get_main_result().trim();
-        if result.is_empty() {
+        // fend echoes back a lot of inputs, such as numbers or some bare identifiers, which are
+        // not worth showing when the query wasn't meant for fend in the first place.
+        if result.is_empty() || (!input.has_prefix() && result.eq_ignore_ascii_case(input.input()))
+        {
             return;
         }
-        let result: Arc<str> = result.into();
+
+        // multi-line results get their first line as the name and the rest as the subtitle
+        let (name, subtitle) = match result.split_once('\n') {
+            Some((name, rest)) => (
+                name.trim(),
+                StringLike::from(
+                    rest.lines()
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("  "),
+                ),
+            ),
+            None => (&*result, StringLike::from(ATTRIBUTION)),
+        };
+        let prioritize = context
+            .config
+            .is_none_or(|v| v["prioritize_results"].as_boolean_default());
+        let value: Arc<str> = result.as_str().into();
         builder
             .add(Entry {
-                name: result.clone().into(),
-                subtitle: "exchange rates by exchangerate-api.com • powered by fend".into(),
-                perfect_match: true,
-                data: CustomData::new(result),
+                name: name.to_string().into(),
+                subtitle,
+                perfect_match: prioritize,
+                weight: 0,
+                data: CustomData::new(FendResult {
+                    input: input.input().trim().into(),
+                    exact: exact.map_or_else(|| value.clone(), Into::into),
+                    value,
+                }),
             })
             .await;
     }
@@ -97,12 +302,24 @@ impl StructPlugin for FendPlugin {
         &self,
         thing: CustomData,
         action: &str,
-        _: crate::PluginContext<'_>,
+        ctx: crate::PluginContext<'_>,
     ) -> Task<Message> {
-        let v = thing.into::<Arc<str>>();
+        if action == "complete" {
+            let v = thing.into::<FendCompletion>();
+            return Task::done(Message::SetSearch(format!("fend {}", v.query)));
+        }
+        let v = thing.into::<FendResult>();
         match action {
-            "copy" => clipboard::write(v.to_string()),
-            "suggest" => Task::done(Message::SetSearch(format!("fend {v}"))),
+            "copy" => {
+                add_to_history(&ctx, &v);
+                clipboard::write(v.value.to_string())
+            }
+            "copy_exact" => {
+                add_to_history(&ctx, &v);
+                clipboard::write(v.exact.to_string())
+            }
+            "suggest" => Task::done(Message::SetSearch(format!("fend {}", v.value))),
+            "rerun" => Task::done(Message::SetSearch(format!("fend {}", v.input))),
             "fend" => {
                 utils::open_link("https://github.com/printfn/fend/");
                 Task::none()
@@ -116,6 +333,22 @@ impl StructPlugin for FendPlugin {
     }
 
     async fn init(&mut self, ctx: crate::PluginContext<'_>) {
+        if let Err(e) = sqlite::await_execute(
+            &ctx.sqlite,
+            "CREATE TABLE IF NOT EXISTS fend_history(id INTEGER PRIMARY KEY AUTOINCREMENT, input TEXT, result TEXT)",
+            [].into(),
+        )
+        .await
+        {
+            log::error!("Failed to create the fend history: {e}");
+        }
+        let enable_currency = ctx
+            .config
+            .is_none_or(|v| v["enable_currency"].as_boolean_default());
+        // without the handler, fend reports currencies as unknown units
+        if !enable_currency {
+            return;
+        }
         self.0
             .write()
             .await
@@ -149,7 +382,149 @@ impl StructPlugin for FendPlugin {
     }
 }
 
+async fn show_history(query: &str, builder: ResultBuilderRef<'_>, ctx: &PluginContext<'_>) {
+    let history = sqlite::await_query_all(
+        &ctx.sqlite,
+        "SELECT input, result FROM fend_history ORDER BY id DESC",
+        [].into(),
+        |row| Ok((row.get::<_, String>("input")?, row.get::<_, String>("result")?)),
+    )
+    .await;
+    let history = match history {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("Failed to read the fend history: {e}");
+            return;
+        }
+    };
+    let query = MatcherInput::new(query.trim().to_string(), true);
+    let iter = history
+        .into_iter()
+        .filter(|(input, result)| query.matches(input) || query.matches(result))
+        .map(|(input, result)| {
+            let input: Arc<str> = input.into();
+            let value: Arc<str> = result.into();
+            Entry::new(
+                value.clone(),
+                input.clone(),
+                CustomData::new(FendResult {
+                    input,
+                    exact: value.clone(),
+                    value,
+                }),
+            )
+        });
+    builder.commit(iter).await;
+}
+
+/// the identifier at the end of the query, if it's being typed
+fn partial_identifier(query: &str) -> Option<&str> {
+    let start = query
+        .rfind(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .map_or(0, |v| v + 1);
+    let partial = &query[start..];
+    partial
+        .starts_with(|c: char| c.is_ascii_alphabetic())
+        .then_some(partial)
+}
+
+/// suggests the functions and constants that start with the identifier being typed. they're
+/// shown below the result of the calculation.
+async fn show_completions(query: &str, builder: ResultBuilderRef<'_>) {
+    let query = query.trim_end();
+    let Some(partial) = partial_identifier(query) else {
+        return;
+    };
+    let before = &query[..query.len() - partial.len()];
+    let iter = IDENTIFIERS
+        .iter()
+        .filter(|(name, _)| name.starts_with(partial) && *name != partial)
+        .map(|(name, description)| Entry {
+            name: (*name).into(),
+            subtitle: (*description).into(),
+            perfect_match: false,
+            weight: -1,
+            data: CustomData::new(FendCompletion {
+                query: format!("{before}{name}").into(),
+            }),
+        });
+    builder.commit(iter).await;
+}
+
+fn add_to_history(ctx: &PluginContext<'_>, result: &FendResult) {
+    let max_len = ctx
+        .config
+        .map_or(100, |v| v["history_length"].as_int_default());
+    // remove the calculation if it was done before so it moves to the top.
+    let mut statements = vec![(
+        "DELETE FROM fend_history WHERE input = ?1 AND result = ?2".into(),
+        [
+            Box::new(result.input.clone()) as Box<_>,
+            Box::new(result.value.clone()) as Box<_>,
+        ]
+        .into(),
+    )];
+    if max_len > 0 {
+        statements.push((
+            "INSERT INTO fend_history (input, result) VALUES (?1, ?2)".into(),
+            [
+                Box::new(result.input.clone()) as Box<_>,
+                Box::new(result.value.clone()) as Box<_>,
+            ]
+            .into(),
+        ));
+        statements.push((
+            "DELETE FROM fend_history WHERE id NOT IN (SELECT id FROM fend_history ORDER BY id DESC LIMIT ?1)".into(),
+            [Box::new(max_len) as Box<_>].into(),
+        ));
+    }
+    sqlite::execute_all(&ctx.sqlite, statements);
+}
+
+/// Returns whether the input is too trivial to be meant for fend (e.g. `1` or `firefox`).
+/// Prefixed queries are never trivial, otherwise it needs at least an operator or multiple words
+/// (e.g. `5 kg` or `10 usd to eur`).
+fn is_trivial_input(input: &MatcherInput) -> bool {
+    if input.has_prefix() {
+        return false;
+    }
+    let query = input.input().trim();
+    let has_operator = query
+        .chars()
+        .any(|c| matches!(c, '+' | '-' | '*' | '/' | '^' | '%' | '(' | ')' | '=' | '!'));
+    !has_operator && !query.contains(char::is_whitespace)
+}
+
 #[derive(Deserialize)]
 struct ExchRateResp {
     rates: HashMap<String, f64>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{FendPlugin, partial_identifier};
+    use crate::{Context, filter_service::collect_entries};
+
+    #[test]
+    fn partial_identifiers() {
+        for (query, expected) in [
+            ("si", Some("si")),
+            ("2 * si", Some("si")),
+            ("sqrt(lo", Some("lo")),
+            ("log1", Some("log1")),
+            ("mixed_f", Some("mixed_f")),
+            ("2 * ", None),
+            ("12", None),
+            ("", None),
+        ] {
+            assert_eq!(partial_identifier(query), expected, "{query:?}");
+        }
+    }
+
+    #[test]
+    fn evaluates() {
+        let entries = collect_entries(&FendPlugin::default(), "1 + 1", false, &Context::test());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "2");
+    }
+}
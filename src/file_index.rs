@@ -215,6 +215,7 @@ async fn main_loop(
 ) -> MainLoopResult {
     // deal with any requests. this is because we do the queue next, and it'd be really stupid to
     // reindex a directory just to add it back to the reindexing queue immediately afterwards.
+    let mut removed_roots = false;
     loop {
         match prev_file_idx_msg
             .take()
@@ -223,25 +224,7 @@ async fn main_loop(
             Ok(FileIndexMessage::Reindex(path)) => _ = queue.insert(ArcPath(path)),
             Ok(FileIndexMessage::SetFileIndex(_)) => unreachable!(),
             Ok(FileIndexMessage::SetConfig(cfg)) => {
-                let mut writer = index.write().await;
-                for entry in &cfg.files.entries {
-                    if let Some(v) = writer.config.get(&*entry.path)
-                        && *v == *entry
-                    {
-                        continue;
-                    }
-                    queue.insert(entry.path.clone());
-                    writer.config.insert(entry.path.0.clone(), entry.clone());
-                }
-                writer
-                    .config
-                    .retain(|k, _| cfg.files.entries.iter().any(|v| v.path.0 == *k));
-                writer.children.retain(|k, _| {
-                    cfg.files
-                        .entries
-                        .iter()
-                        .any(|v| v.path == *k && !queue.contains(k))
-                });
+                removed_roots |= reconcile_config(&index, &cfg, queue).await;
             }
             Err(TryRecvError::Empty) => break,
             Err(TryRecvError::Disconnected) => return MainLoopResult::Stop,
@@ -253,7 +236,7 @@ async fn main_loop(
         FileIndex::index(index.clone(), &path).await;
         true
     } else {
-        false
+        removed_roots
     };
     let result = if queue.is_empty() {
         MainLoopResult::Idle
@@ -310,7 +293,12 @@ async fn main_loop(
                         continue;
                     };
                     let path = ArcPath((&**path).into());
-                    if data.paths.insert(path.clone()) && kind == CreateKind::Folder {
+                    if !data.paths.insert(path.clone()) {
+                        continue;
+                    }
+                    if kind == CreateKind::File {
+                        data.insert_extension(path);
+                    } else {
                         if data.watched
                             && let Err(e) = watcher.watch(&path, RecursiveMode::NonRecursive)
                         {
@@ -328,6 +316,7 @@ async fn main_loop(
                     if !data.paths.remove(&**path) {
                         continue;
                     }
+                    data.remove_extension(path);
                     if !data.directories.remove(&**path) {
                         continue;
                     }
@@ -358,6 +347,84 @@ async fn main_loop(
     result
 }
 
+/// applies a new config to the file index. roots that were added or whose filter changed are
+/// queued for indexing, roots that were removed are unwatched and dropped from the index and
+/// roots whose watch flag changed are (un)watched without reindexing them. returns if any roots
+/// were removed.
+async fn reconcile_config(
+    index: &RwLock<FileIndex>,
+    config: &Config,
+    queue: &mut HashSet<ArcPath>,
+) -> bool {
+    let mut writer = index.write().await;
+    let mut seen = HashSet::new();
+    let mut watch_changes = Vec::new();
+    for entry in &config.files.entries {
+        if !seen.insert(entry.path.clone()) {
+            log::error!(
+                "The config contains multiple entries for {}, only the first one is used.\nPlease edit the config at {}",
+                entry.path.display(),
+                CONFIG_FILE.display()
+            );
+            continue;
+        }
+        let old = writer.config.insert(entry.path.0.clone(), entry.clone());
+        match old {
+            Some(old) if old == *entry => {}
+            Some(old) if old.filter == entry.filter => {
+                let Some(data) = writer.children.get_mut(&*entry.path) else {
+                    queue.insert(entry.path.clone());
+                    continue;
+                };
+                if old.reindex_every != entry.reindex_every {
+                    data.next_scan = entry.reindex_every.map(|v| SystemTime::now() + v);
+                }
+                if old.watch != entry.watch {
+                    watch_changes.push((entry.path.clone(), entry.watch));
+                }
+            }
+            _ => {
+                log::debug!("the config for {} changed", entry.path.display());
+                queue.insert(entry.path.clone());
+            }
+        }
+    }
+    if !watch_changes.is_empty()
+        && let Some(watcher) = writer.watcher.clone()
+    {
+        let mut watcher = watcher.write().await;
+        for (path, watch) in watch_changes {
+            // the path can't be queued here, as it has to have been indexed before
+            let Some(data) = writer.children.get_mut(&*path) else {
+                continue;
+            };
+            if watch {
+                log::debug!("watching {}", path.display());
+                data.start_watching(&mut watcher);
+            } else {
+                log::debug!("no longer watching {}", path.display());
+                data.stop_watching(&mut watcher);
+            }
+        }
+    }
+    let removed = writer
+        .config
+        .keys()
+        .filter(|path| !seen.contains(&***path))
+        .cloned()
+        .collect::<Vec<_>>();
+    for path in &removed {
+        writer.config.remove(path);
+        queue.remove(&**path);
+    }
+    drop(writer);
+    for path in &removed {
+        log::debug!("{} was removed from the config", path.display());
+        FileIndex::remove(index, path).await;
+    }
+    !removed.is_empty()
+}
+
 pub static INDEX_FILE_DIR: LazyLock<PathBuf> =
     LazyLock::new(|| utils::DATA_DIR.join("file_index.toml"));
 
@@ -365,7 +432,9 @@ async fn load_fileindex(
     event_handler: impl Fn(Result<notify::Event, notify::Error>) + Send + 'static,
     file_index: &RwLock<FileIndex>,
 ) -> bool {
-    let children = if let Ok(data) = tokio::fs::read_to_string(&*INDEX_FILE_DIR).await {
+    let mut children: HashMap<ArcPath, FileIndexData> = if let Ok(data) =
+        tokio::fs::read_to_string(&*INDEX_FILE_DIR).await
+    {
         match toml::from_str(&data) {
             Ok(v) => v,
             Err(e) => {
@@ -388,6 +457,10 @@ async fn load_fileindex(
     };
     let mut writer = file_index.write().await;
     writer.watcher = Some(watcher);
+    // the extensions aren't stored, as they're quick to derive from the paths
+    for data in children.values_mut() {
+        data.index_extensions();
+    }
     writer.children.extend(children);
     true
 }
@@ -553,16 +626,172 @@ impl FileIndex {
     }
 }
 
+/// what kind of file an extension belongs to, for queries like `file type:image`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCategory {
+    Image,
+    Video,
+    Audio,
+    Document,
+    Archive,
+    Code,
+}
+
+impl FileCategory {
+    /// the category with this name, which may be in plural (e.g. `images`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_suffix('s').unwrap_or(name) {
+            "image" => Some(Self::Image),
+            "video" => Some(Self::Video),
+            "audio" => Some(Self::Audio),
+            "document" | "doc" => Some(Self::Document),
+            "archive" => Some(Self::Archive),
+            "code" => Some(Self::Code),
+            _ => None,
+        }
+    }
+
+    /// the category of a lowercase extension, if it's a common one
+    pub fn of_extension(extension: &str) -> Option<Self> {
+        Some(match extension {
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "tif" | "tiff" | "ico"
+            | "heic" | "avif" => Self::Image,
+            "mp4" | "mkv" | "webm" | "avi" | "mov" | "wmv" | "flv" | "m4v" | "mpg" | "mpeg" => {
+                Self::Video
+            }
+            "mp3" | "flac" | "wav" | "ogg" | "opus" | "m4a" | "aac" | "wma" => Self::Audio,
+            "pdf" | "doc" | "docx" | "odt" | "rtf" | "txt" | "md" | "tex" | "epub" | "xls"
+            | "xlsx" | "ods" | "csv" | "ppt" | "pptx" | "odp" => Self::Document,
+            "zip" | "tar" | "gz" | "tgz" | "xz" | "bz2" | "7z" | "rar" | "zst" => Self::Archive,
+            "rs" | "c" | "h" | "cpp" | "hpp" | "py" | "js" | "ts" | "java" | "go" | "lua"
+            | "sh" | "rb" | "toml" | "json" | "yaml" | "yml" | "html" | "css" => Self::Code,
+            _ => return None,
+        })
+    }
+}
+
+/// narrows the paths of an index down, see [`FileIndexData::paths_of_kind`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathKind {
+    /// files with this lowercase extension
+    Extension(Box<str>),
+    /// files with an extension of this category
+    Category(FileCategory),
+    Directory,
+}
+
+/// the lowercase extension of a path, if it's valid utf-8
+fn extension(path: &Path) -> Option<Box<str>> {
+    path.extension()?.to_str().map(|v| v.to_lowercase().into())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct FileIndexData {
     pub paths: HashSet<ArcPath>,
     directories: HashSet<ArcPath>,
+    /// the files grouped by their lowercase extension, so filtering by type doesn't have to look
+    /// at every path
+    #[serde(skip)]
+    extensions: HashMap<Box<str>, HashSet<ArcPath>>,
     next_scan: Option<SystemTime>,
     watched: bool,
 }
 
 impl FileIndexData {
+    /// whether `path` was a directory when it was indexed
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.directories.contains(path)
+    }
+
+    /// groups the files by their extension
+    fn index_extensions(&mut self) {
+        self.extensions.clear();
+        let files = self
+            .paths
+            .iter()
+            .filter(|v| !self.directories.contains(*v))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in files {
+            self.insert_extension(path);
+        }
+    }
+
+    fn insert_extension(&mut self, path: ArcPath) {
+        if let Some(extension) = extension(&path) {
+            self.extensions.entry(extension).or_default().insert(path);
+        }
+    }
+
+    fn remove_extension(&mut self, path: &Path) {
+        let Some(extension) = extension(path) else {
+            return;
+        };
+        if let Some(paths) = self.extensions.get_mut(&extension) {
+            paths.remove(path);
+            if paths.is_empty() {
+                self.extensions.remove(&extension);
+            }
+        }
+    }
+
+    /// whether a file with the lowercase extension was indexed
+    pub fn has_extension(&self, extension: &str) -> bool {
+        self.extensions.contains_key(extension)
+    }
+
+    /// the paths of the kind, or all of them
+    pub fn paths_of_kind<'a>(
+        &'a self,
+        kind: Option<&'a PathKind>,
+    ) -> Box<dyn Iterator<Item = &'a ArcPath> + Send + 'a> {
+        match kind {
+            None => Box::new(self.paths.iter()),
+            Some(PathKind::Directory) => Box::new(self.directories.iter()),
+            Some(PathKind::Extension(extension)) => {
+                Box::new(self.extensions.get(extension).into_iter().flatten())
+            }
+            Some(&PathKind::Category(category)) => Box::new(
+                self.extensions
+                    .iter()
+                    .filter(move |(extension, _)| {
+                        FileCategory::of_extension(extension) == Some(category)
+                    })
+                    .flat_map(|(_, paths)| paths),
+            ),
+        }
+    }
+
+    /// how many paths [`Self::paths_of_kind`] returns, without going through them
+    pub fn count_of_kind(&self, kind: Option<&PathKind>) -> usize {
+        match kind {
+            None => self.paths.len(),
+            Some(PathKind::Directory) => self.directories.len(),
+            Some(PathKind::Extension(extension)) => {
+                self.extensions.get(extension).map_or(0, HashSet::len)
+            }
+            Some(&PathKind::Category(category)) => self
+                .extensions
+                .iter()
+                .filter(|(extension, _)| FileCategory::of_extension(extension) == Some(category))
+                .map(|(_, paths)| paths.len())
+                .sum(),
+        }
+    }
+
+    pub fn stop_watching(&mut self, watcher: &mut RecommendedWatcher) {
+        self.watched = false;
+        for dir in &self.directories {
+            if let Err(e) = watcher.unwatch(dir)
+                && !matches!(e.kind, ErrorKind::WatchNotFound)
+            {
+                log::debug!("Failed to unwatch {}: {e:?}", dir.display());
+            }
+        }
+    }
+
     pub fn start_watching(&mut self, watcher: &mut RecommendedWatcher) {
+        self.watched = true;
         let mut did_err = false;
         self.directories.retain(|dir| {
             if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
@@ -661,12 +890,15 @@ impl FileIndexer {
 
     pub fn into_data(self, next_scan: Option<SystemTime>) -> FileIndexData {
         assert!(self.queue.is_empty());
-        FileIndexData {
+        let mut data = FileIndexData {
             paths: self.entries,
             directories: self.dirs,
+            extensions: HashMap::new(),
             next_scan,
             watched: self.watcher.is_some(),
-        }
+        };
+        data.index_extensions();
+        data
     }
 
     pub async fn cycle(&mut self) -> bool {
@@ -1,41 +1,243 @@
 // File plugin to search and index the entire drive (except a few directories)
 
-use std::{ffi::OsStr, path::Path, process::Command, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    ffi::OsStr,
+    num::NonZeroUsize,
+    path::Path,
+    process::Command,
+    sync::{Arc, atomic::Ordering},
+};
 
 use iced::{
-    Task,
+    Task, clipboard,
     keyboard::{Key, Modifiers, key::Named},
 };
+use regex::Regex;
 
 use crate::{
     Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef, StructPlugin,
-    matcher::MatcherInput, plugin::StringLike, utils,
+    config::ArcPath,
+    file_index::{FileCategory, FileIndex, FileIndexData, PathKind},
+    matcher::MatcherInput,
+    plugin::StringLike,
+    run_plugin::{self, RunPlugin},
+    utils,
 };
 
 #[derive(Default)]
 pub struct FilePlugin;
 
+/// indices with less paths than this are matched on the collector thread instead of the blocking
+/// thread pool
+const PARALLEL_THRESHOLD: usize = 16_384;
+/// how many paths a shard matches before checking if the search was cancelled
+const CHUNK_SIZE: usize = 1024;
+/// how much the weight drops for every byte the match starts later in the file name
+const POSITION_WEIGHT: i32 = 16;
+/// how much the weight drops for every directory the file is nested in
+const DEPTH_WEIGHT: i32 = 1;
+
+const OPEN: Action = Action::default("Open", "open");
+// plain and alt+enter submit and shift+enter submits without closing, so these need ctrl.
+const COPY_PATH: Action = Action::new(
+    "Copy path",
+    "copy_path",
+    (Modifiers::CTRL.union(Modifiers::SHIFT), Key::Named(Named::Enter)),
+);
+const COPY_NAME: Action = Action::new(
+    "Copy name",
+    "copy_name",
+    (Modifiers::CTRL.union(Modifiers::ALT), Key::Named(Named::Enter)),
+);
+const DIRECTORY_ACTIONS: &[Action] = &[
+    OPEN,
+    Action::new(
+        "Open in terminal",
+        "terminal",
+        (Modifiers::CTRL, Key::Named(Named::Enter)),
+    ),
+    COPY_PATH,
+    COPY_NAME,
+];
+// a terminal can't be opened in a file
+const FILE_ACTIONS: &[Action] = &[
+    OPEN,
+    Action::new(
+        "Open containing folder",
+        "folder",
+        (Modifiers::CTRL, Key::Named(Named::Enter)),
+    ),
+    // picks the application in the run plugin
+    Action::without_shortcut("Open with…", "open_with").keep_open(),
+    COPY_PATH,
+    COPY_NAME,
+];
+
+/// matches near the start of the file name and files that aren't nested deeply rank higher
+fn weight(path: &Path, start: usize) -> i32 {
+    let position = i32::try_from(start).unwrap_or(i32::MAX);
+    let depth = i32::try_from(path.components().count()).unwrap_or(i32::MAX);
+    position
+        .saturating_mul(POSITION_WEIGHT)
+        .saturating_add(depth.saturating_mul(DEPTH_WEIGHT))
+        .saturating_neg()
+}
+
+/// what the file names are matched against
+enum Pattern {
+    Fuzzy(MatcherInput),
+    /// from a `file /regex/` query
+    Regex(Regex),
+    /// from a query that only narrows down the type, like `file .pdf`
+    Any,
+}
+
+impl Pattern {
+    /// returns if the match is perfect and where it starts in the file name
+    fn matches(&self, path: &Path) -> Option<(bool, usize)> {
+        match self {
+            Pattern::Fuzzy(input) => path_matches(input, path),
+            Pattern::Regex(regex) => {
+                let name = path.file_name()?.to_str()?;
+                let found = regex.find(name)?;
+                Some((found.len() == name.len(), found.start()))
+            }
+            Pattern::Any => Some((false, 0)),
+        }
+    }
+}
+
+/// the regex of a `/regex/` query, if it is one
+fn regex_pattern(query: &str) -> Option<&str> {
+    query
+        .strip_prefix('/')?
+        .strip_suffix('/')
+        .filter(|pattern| !pattern.is_empty())
+}
+
+/// compiles the regex of a `/regex/` query. it's case-sensitive unless it starts with `(?i)`.
+fn compile_regex(query: &str) -> Option<Result<Regex, regex::Error>> {
+    regex_pattern(query).map(Regex::new)
+}
+
+/// the kind of a `.ext` or `type:kind` word
+fn parse_kind(word: &str) -> Option<PathKind> {
+    let word = word.to_lowercase();
+    let extension = |v: &str| {
+        (!v.is_empty() && v.chars().all(char::is_alphanumeric))
+            .then(|| PathKind::Extension(v.into()))
+    };
+    let Some(name) = word.strip_prefix("type:") else {
+        return extension(word.strip_prefix('.')?);
+    };
+    match name {
+        "dir" | "dirs" | "directory" | "directories" | "folder" | "folders" => {
+            Some(PathKind::Directory)
+        }
+        _ => FileCategory::from_name(name)
+            .map(PathKind::Category)
+            .or_else(|| extension(name.trim_start_matches('.'))),
+    }
+}
+
+/// splits the first `.ext` or `type:kind` word off a query like `file type:image report`, which
+/// narrows the search down to paths of that kind. the rest is matched against their names.
+fn path_kind(query: &str) -> Option<(PathKind, String)> {
+    let mut kind = None;
+    let mut rest = Vec::new();
+    for word in query.split_whitespace() {
+        if kind.is_none()
+            && let Some(v) = parse_kind(word)
+        {
+            kind = Some(v);
+        } else {
+            rest.push(word);
+        }
+    }
+    Some((kind?, rest.join(" ")))
+}
+
+/// the entry shown instead of the results when the regex of the query doesn't compile
+#[derive(Clone)]
+struct InvalidRegex;
+
+/// an indexed path, stored in the entries of this plugin
+#[derive(Clone)]
+struct FileEntry {
+    path: Arc<Path>,
+    /// recorded while indexing, so the actions of an entry don't need to touch the filesystem
+    is_dir: bool,
+}
+
+/// all indexed paths of the kind along with the index they belong to
+fn indexed_paths<'a>(
+    index: &'a FileIndex,
+    kind: Option<&'a PathKind>,
+) -> impl Iterator<Item = (&'a ArcPath, &'a FileIndexData)> {
+    index
+        .children
+        .values()
+        .flat_map(move |data| data.paths_of_kind(kind).map(move |path| (path, data)))
+}
+
+/// replaces the home directory at the start of `dir` with `~`
+fn abbreviate_home(dir: &str) -> Option<String> {
+    let home = utils::HOME_DIR.as_deref()?.to_str()?.trim_end_matches('/');
+    let rest = dir.strip_prefix(home)?;
+    (!home.is_empty() && (rest.is_empty() || rest.starts_with('/'))).then(|| format!("~{rest}"))
+}
+
+/// the data of an entry named by its path, see [`CustomData::deferred`]
+fn entry_data(name: &StringLike, is_dir: bool) -> CustomData {
+    CustomData::new(FileEntry {
+        path: name
+            .full_path()
+            .expect("the name is a part of the path")
+            .clone(),
+        is_dir,
+    })
+}
+
 fn iter<'a>(
-    input: &MatcherInput,
-    iter: impl Iterator<Item = &'a Arc<Path>>,
+    pattern: &Pattern,
+    abbreviate: bool,
+    iter: impl Iterator<Item = (&'a ArcPath, &'a FileIndexData)>,
 ) -> impl Iterator<Item = Entry> {
-    iter.filter_map(|path| path_matches(input, path).map(|v| (path, v)))
-        .map(|(v, perfect_match)| {
+    iter.filter_map(|(path, data)| pattern.matches(path).map(|v| (path, data, v)))
+        .map(|(v, data, (perfect_match, start))| {
             (
-                v.clone(),
+                FileEntry {
+                    path: v.0.clone(),
+                    is_dir: data.is_dir(v),
+                },
                 v.file_name().map_or(0, OsStr::len),
                 perfect_match,
+                start,
             )
         })
-        .map(|(v, filename_len, perfect_match)| {
-            let mut name = StringLike::from(v.clone());
+        .map(|(v, filename_len, perfect_match, start)| {
+            let mut name = StringLike::from(v.path.clone());
             name.substr((name.len() - filename_len) as u16..);
-            let mut subtitle = StringLike::from(v.clone());
+            let mut subtitle = StringLike::from(v.path.clone());
             subtitle.substr(..(subtitle.len() - filename_len) as u16);
+            // the prefix can't be cut off the shared path, so the subtitle gets its own string
+            if abbreviate && let Some(abbreviated) = abbreviate_home(&subtitle) {
+                subtitle = StringLike::from(abbreviated);
+            }
+            let weight = weight(&v.path, start);
+            // the name is a part of the path, so the data can be built from it later on
+            let data = match name.full_path() {
+                Some(_) if v.is_dir => CustomData::deferred(|name| entry_data(name, true)),
+                Some(_) => CustomData::deferred(|name| entry_data(name, false)),
+                None => CustomData::new(v),
+            };
             Entry {
                 name,
                 subtitle,
-                data: CustomData::new(v),
+                weight,
+                data,
                 perfect_match,
             }
         })
@@ -46,30 +248,139 @@ impl StructPlugin for FilePlugin {
         "file"
     }
 
+    fn description() -> &'static str {
+        "Search the indexed files and directories, or match their names with `file /regex/`. \
+         `.pdf` or `type:image` only searches files of that type"
+    }
+
     async fn get_for_values(
         &self,
         input: &MatcherInput,
         builder: ResultBuilderRef<'_>,
         context: PluginContext<'_>,
     ) {
-        let reader = context.file_index.read().await;
-        let iter = iter(
-            input,
-            reader
-                .children
-                .values()
-                .flat_map(|v| v.paths.iter())
-                .map(|v| &v.0),
-        );
-        builder.commit(iter).await;
+        let reader = context.file_index.clone().read_owned().await;
+        // the regex needs the query in its original case, as e.g. `\D` isn't `\d`
+        let mut kind = path_kind(input.original()).filter(|_| input.has_prefix());
+        // `.bashrc` is a name rather than an extension, unless a file has it
+        if let Some((PathKind::Extension(extension), _)) = &kind
+            && !reader.children.values().any(|v| v.has_extension(extension))
+        {
+            kind = None;
+        }
+        let (kind, name) = kind.unzip();
+        let query = name.as_deref().unwrap_or(input.original());
+        let pattern = match compile_regex(query).filter(|_| input.has_prefix()) {
+            Some(Ok(regex)) => Pattern::Regex(regex),
+            Some(Err(e)) => {
+                // syntax errors point at the mistake over multiple lines, ending in the reason
+                let e = e.to_string();
+                let reason = e.lines().last().unwrap_or_default();
+                let reason = reason.strip_prefix("error: ").unwrap_or(reason);
+                builder
+                    .add(Entry::new(
+                        "Invalid regex",
+                        reason.to_string(),
+                        CustomData::new(InvalidRegex),
+                    ))
+                    .await;
+                return;
+            }
+            None if name.is_none() => Pattern::Fuzzy(input.clone()),
+            None if query.trim().is_empty() => Pattern::Any,
+            None => Pattern::Fuzzy(MatcherInput::new(query.to_string(), true)),
+        };
+        let abbreviate = context.global_config.files.abbreviate_home;
+        let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let amount = reader
+            .children
+            .values()
+            .map(|v| v.count_of_kind(kind.as_ref()))
+            .sum::<usize>();
+        if threads == 1 || amount < PARALLEL_THRESHOLD {
+            let paths = indexed_paths(&reader, kind.as_ref());
+            builder.commit(iter(&pattern, abbreviate, paths)).await;
+            return;
+        }
+        let reader = Arc::new(reader);
+        let pattern = Arc::new(pattern);
+        // the paths are collected once along with the position of the index they belong to, so a
+        // shard is a slice of them instead of having to walk the index up to its start
+        let paths = reader
+            .children
+            .values()
+            .enumerate()
+            .flat_map(|(root, data)| {
+                data.paths_of_kind(kind.as_ref())
+                    .map(move |path| (path.clone(), root))
+            })
+            .collect::<Arc<[_]>>();
+        // matching is cpu-bound, so the paths are split into shards that are matched on the
+        // blocking thread pool. every shard checks if the search was cancelled every few paths.
+        let shard_size = paths.len().div_ceil(threads).max(1);
+        let handles = (0..paths.len())
+            .step_by(shard_size)
+            .map(|start| {
+                let reader = reader.clone();
+                let paths = paths.clone();
+                let pattern = pattern.clone();
+                let should_stop = builder.get_should_stop();
+                tokio::task::spawn_blocking(move || {
+                    // the order of the indices doesn't change while the reader is held
+                    let roots = reader.children.values().collect::<Vec<_>>();
+                    let end = paths.len().min(start + shard_size);
+                    let shard = paths[start..end]
+                        .iter()
+                        .enumerate()
+                        .take_while(|(i, _)| {
+                            i % CHUNK_SIZE != 0 || !should_stop.load(Ordering::Relaxed)
+                        })
+                        .map(|(_, (path, root))| (path, roots[*root]));
+                    iter(&pattern, abbreviate, shard).collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            match handle.await {
+                Ok(entries) => results.push(entries),
+                Err(e) => log::error!("failed to match the files: {e}"),
+            }
+        }
+        builder.commit(results.into_iter().flatten()).await;
     }
 
     async fn init(&mut self, _: PluginContext<'_>) {}
 
     fn handle_pre(&self, thing: CustomData, action: &str, _: PluginContext<'_>) -> Task<Message> {
-        let path = thing.into::<Arc<Path>>();
-        if action == "open" {
+        let Some(entry) = thing.get::<FileEntry>() else {
+            return Task::none();
+        };
+        let path = entry.path.clone();
+        if action == "copy_path" {
+            return clipboard::write(path.to_string_lossy().into_owned());
+        } else if action == "copy_name" {
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            return clipboard::write(name.to_string_lossy().into_owned());
+        } else if action == "open" {
             utils::open_file(path);
+        } else if action == "open_with" {
+            // xdg-mime blocks, so it's asked on the blocking thread pool
+            let task = async move {
+                tokio::task::spawn_blocking(move || {
+                    let mime = utils::mime_type(&path);
+                    let mime = mime.unwrap_or_else(|| "application/octet-stream".into());
+                    run_plugin::open_with(path, mime);
+                })
+                .await
+            };
+            return Task::perform(task, |_| {
+                Message::SetSearch(format!("{} ", RunPlugin::prefix()))
+            });
+        } else if action == "folder" {
+            if let Some(parent) = path.parent() {
+                utils::open_file(parent);
+            }
         } else if let Some(terminal) = &*utils::TERMINAL {
             let mut cmd = Command::new(terminal);
             cmd.current_dir(path);
@@ -79,21 +390,158 @@ impl StructPlugin for FilePlugin {
     }
 
     fn actions(&self) -> &'static [Action] {
-        const {
-            &[
-                Action::default("Open", "open"),
-                Action::new(
-                    "Open in terminal",
-                    "terminal",
-                    (Modifiers::CTRL, Key::Named(Named::Enter)),
-                ),
-            ]
+        DIRECTORY_ACTIONS
+    }
+
+    fn completions(&self, query: &str, context: PluginContext<'_>) -> Vec<String> {
+        // this runs on the ui thread, so it doesn't wait for the indexer
+        let index = context.file_index.try_read().ok();
+        complete_path(query, index.as_deref())
+    }
+
+    fn actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        match data.get::<FileEntry>() {
+            // the invalid regex entry can't be run
+            None => Cow::Borrowed(&[]),
+            Some(entry) if entry.is_dir => Cow::Borrowed(DIRECTORY_ACTIONS),
+            Some(_) => Cow::Borrowed(FILE_ACTIONS),
+        }
+    }
+}
+
+/// completes the last component of a query like `file ~/Doc` like a shell: a single match is
+/// completed fully, multiple ones only as far as they have in common. the names are only taken
+/// from the index, as this runs on the ui thread, so directories that aren't indexed aren't
+/// completed. hidden entries are only completed once the component starts with a dot.
+fn complete_path(query: &str, index: Option<&FileIndex>) -> Vec<String> {
+    let path = query.trim_start();
+    let Some(slash) = path.rfind('/') else {
+        return if path == "~" {
+            vec![format!("{query}/")]
+        } else {
+            Vec::new()
+        };
+    };
+    let (dir, partial) = path.split_at(slash + 1);
+    let expanded = if let Some(rest) = dir.strip_prefix("~/") {
+        let Some(home) = &*utils::HOME_DIR else {
+            return Vec::new();
+        };
+        home.join(rest)
+    } else if dir.starts_with('/') {
+        dir.into()
+    } else {
+        return Vec::new();
+    };
+    let completes = |name: &str| {
+        name.starts_with(partial) && (!name.starts_with('.') || partial.starts_with('.'))
+    };
+    let names = index
+        .map(|index| indexed_names(index, &expanded, completes))
+        .unwrap_or_default();
+    let mut names = names.into_iter();
+    let Some(first) = names.next() else {
+        return Vec::new();
+    };
+    let common = names.fold(first.as_str(), |common, name| {
+        let len = common
+            .char_indices()
+            .zip(name.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(common.len().min(name.len()), |((i, _), _)| i);
+        &common[..len]
+    });
+    let before = &query[..query.len() - partial.len()];
+    vec![format!("{before}{common}")]
+}
+
+/// the names of the indexed paths directly inside `dir` that `completes` accepts, with a trailing
+/// slash for directories
+fn indexed_names(
+    index: &FileIndex,
+    dir: &Path,
+    completes: impl Fn(&str) -> bool,
+) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let roots = index
+        .children
+        .iter()
+        .filter(|(root, _)| root.starts_with(dir) || dir.starts_with(&***root));
+    for (_, data) in roots {
+        for path in &data.paths {
+            let Ok(rest) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let mut components = rest.components();
+            let Some(name) = components.next().and_then(|v| v.as_os_str().to_str()) else {
+                continue;
+            };
+            if !completes(name) {
+                continue;
+            }
+            if components.next().is_some() || data.is_dir(path) {
+                names.insert(format!("{name}/"));
+            } else {
+                names.insert(name.to_string());
+            }
         }
     }
+    names
 }
 
-fn path_matches(input: &MatcherInput, path: &Path) -> Option<bool> {
+fn path_matches(input: &MatcherInput, path: &Path) -> Option<(bool, usize)> {
     path.file_name()
         .and_then(OsStr::to_str)
-        .and_then(|v| input.matches_perfect(v))
+        .and_then(|v| input.matches_perfect_at(v))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compile_regex, path_kind};
+    use crate::file_index::{FileCategory, PathKind};
+    use crate::matcher::MatcherInput;
+
+    #[test]
+    fn kinds() {
+        let cases = [
+            (".pdf", Some((PathKind::Extension("pdf".into()), ""))),
+            (
+                "type:image report",
+                Some((PathKind::Category(FileCategory::Image), "report")),
+            ),
+            (
+                "notes type:docs",
+                Some((PathKind::Category(FileCategory::Document), "notes")),
+            ),
+            ("type:dir src", Some((PathKind::Directory, "src"))),
+            (
+                "type:.rs main",
+                Some((PathKind::Extension("rs".into()), "main")),
+            ),
+            (
+                "report .pdf .txt",
+                Some((PathKind::Extension("pdf".into()), "report .txt")),
+            ),
+            ("report", None),
+            ("/re.*gex/", None),
+        ];
+        for (query, expected) in cases {
+            let kind = path_kind(query);
+            let kind = kind
+                .as_ref()
+                .map(|(kind, rest)| (kind.clone(), rest.as_str()));
+            assert_eq!(kind, expected, "{query:?}");
+        }
+    }
+
+    #[test]
+    fn regex_keeps_case() {
+        let input = MatcherInput::new(r"/^\D+$/".to_string(), true);
+        let regex = compile_regex(input.original()).unwrap().unwrap();
+        assert!(regex.is_match("notes"));
+        assert!(!regex.is_match("2024"));
+        let input = MatcherInput::new("/Notes/".to_string(), true);
+        let regex = compile_regex(input.original()).unwrap().unwrap();
+        assert!(!regex.is_match("notes"));
+    }
 }
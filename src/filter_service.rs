@@ -1,9 +1,11 @@
 use std::{
+    borrow::Borrow,
     cmp,
+    collections::HashMap,
     pin::{Pin, pin},
     sync::{
         Arc,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
     },
     task::Poll,
     time::Duration,
@@ -20,7 +22,10 @@ use iced::{
 };
 use tokio::sync::RwLock;
 
-use crate::{AnyPlugin, Context, Entry, GenericEntry, PluginContext, matcher::MatcherInput};
+use crate::{
+    AnyPlugin, Context, Entry, GenericEntry, PluginContext, StructPlugin,
+    history_plugin::HistoryPlugin, matcher::MatcherInput,
+};
 
 #[derive(Clone, Copy)]
 pub struct ResultBuilderRef<'a> {
@@ -35,6 +40,9 @@ impl<'a> ResultBuilderRef<'a> {
 
     /// returns false if you should stop adding entries.
     pub async fn add(&self, entry: Entry) -> bool {
+        if self.is_excluded() {
+            return false;
+        }
         self.builder
             .commit(std::iter::once(GenericEntry {
                 name: entry.name,
@@ -42,12 +50,16 @@ impl<'a> ResultBuilderRef<'a> {
                 plugin: self.plugin_id,
                 data: entry.data,
                 perfect_match: entry.perfect_match,
+                weight: entry.weight,
             }))
             .await
     }
 
     /// returns false if you should stop adding entries.
     pub async fn commit(&self, iter: impl Iterator<Item = Entry>) -> bool {
+        if self.is_excluded() {
+            return false;
+        }
         self.builder
             .commit(iter.map(|entry| GenericEntry {
                 name: entry.name,
@@ -55,19 +67,154 @@ impl<'a> ResultBuilderRef<'a> {
                 plugin: self.plugin_id,
                 data: entry.data,
                 perfect_match: entry.perfect_match,
+                weight: entry.weight,
             }))
             .await
     }
 
     pub fn should_stop(&self) -> bool {
-        self.builder.should_stop()
+        self.builder.should_stop() || self.is_excluded()
+    }
+
+    /// only shows the results of this plugin for the current query, e.g. when it's certain the
+    /// query was meant for it. results of other plugins that were already shown disappear, so
+    /// this should be called as early as possible. if multiple plugins claim a query, the one
+    /// that was registered first wins.
+    pub fn claim_exclusive(&self) {
+        self.builder
+            .exclusive
+            .fetch_min(self.plugin_id, Ordering::Relaxed);
+    }
+
+    /// whether another plugin claimed the query
+    fn is_excluded(&self) -> bool {
+        self.builder
+            .exclusive_plugin()
+            .is_some_and(|id| id != self.plugin_id)
+    }
+
+    /// the flag behind [`Self::should_stop`], for work that is moved to another thread
+    pub fn get_should_stop(&self) -> Arc<AtomicBool> {
+        self.builder.get_should_stop()
     }
 }
 
-#[derive(Default)]
+/// how many results are kept of every plugin for a query, and how many are shown. the worst ones
+/// are dropped once there are more, so plugins flooding results don't make every sample slower.
+const MAX_RESULTS: usize = 1000;
+
 pub struct ResultBuilder {
-    results: RwLock<Vec<GenericEntry>>,
+    results: RwLock<Results>,
     should_stop: Arc<AtomicBool>,
+    /// the plugin that claimed the query, `usize::MAX` if none did
+    exclusive: AtomicUsize,
+}
+
+/// the best [`MAX_RESULTS`] entries every plugin added. they're kept per plugin, so one plugin
+/// can't push out the results of a plugin that claims the query later on.
+#[derive(Default)]
+struct Results {
+    /// the entries of every plugin, best first
+    best: HashMap<usize, Vec<Ranked>>,
+    /// how many entries were added, including the dropped ones
+    added: usize,
+    /// the exclusive plugin the results of other plugins were last removed for
+    purged_for: Option<usize>,
+}
+
+/// an entry ordered by how good of a match it is. perfect matches come first, then the ones
+/// with the highest weight and then the ones that were added first.
+struct Ranked {
+    entry: GenericEntry,
+    index: usize,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.entry
+            .perfect_match
+            .cmp(&other.entry.perfect_match)
+            .then(self.entry.weight.cmp(&other.entry.weight))
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl Eq for Ranked {}
+
+impl Results {
+    fn push(&mut self, entry: GenericEntry) {
+        let ranked = Ranked {
+            entry,
+            index: self.added,
+        };
+        self.added += 1;
+        let entries = self.best.entry(ranked.entry.plugin).or_default();
+        let pos = entries.partition_point(|other| *other > ranked);
+        if pos < MAX_RESULTS {
+            entries.truncate(MAX_RESULTS - 1);
+            entries.insert(pos, ranked);
+        }
+    }
+
+    /// removes the results of every plugin but the exclusive one
+    fn purge(&mut self, exclusive: usize) {
+        if self.purged_for != Some(exclusive) {
+            self.purged_for = Some(exclusive);
+            self.best.retain(|plugin, _| *plugin == exclusive);
+        }
+    }
+
+    /// the results, best first
+    fn sorted(self) -> Vec<GenericEntry> {
+        merge(self.best.into_values().map(Vec::into_iter))
+            .map(|v| v.entry)
+            .collect()
+    }
+
+    /// the results, best first, without taking them
+    fn to_sorted(&self) -> Vec<GenericEntry> {
+        merge(self.best.values().map(|v| v.iter()))
+            .map(|v| v.entry.clone())
+            .collect()
+    }
+}
+
+/// merges lists that are sorted best first into the best [`MAX_RESULTS`] of their entries
+fn merge<T: Borrow<Ranked>>(
+    lists: impl Iterator<Item = impl Iterator<Item = T>>,
+) -> impl Iterator<Item = T> {
+    let mut lists = lists.map(Iterator::peekable).collect::<Vec<_>>();
+    std::iter::from_fn(move || {
+        let (best, _) = lists
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, list)| Some((i, list.peek()?)))
+            .max_by(|(_, a), (_, b)| (*a).borrow().cmp((*b).borrow()))?;
+        lists[best].next()
+    })
+    .take(MAX_RESULTS)
+}
+
+impl Default for ResultBuilder {
+    fn default() -> Self {
+        Self {
+            results: RwLock::default(),
+            should_stop: Arc::default(),
+            exclusive: AtomicUsize::new(usize::MAX),
+        }
+    }
 }
 
 impl ResultBuilder {
@@ -77,6 +224,9 @@ impl ResultBuilder {
             return false;
         }
         let mut writer = self.results.write().await;
+        if let Some(plugin) = self.exclusive_plugin() {
+            writer.purge(plugin);
+        }
         for entry in iter {
             writer.push(entry);
             if self.should_stop.load(Ordering::Relaxed) {
@@ -86,14 +236,15 @@ impl ResultBuilder {
         true
     }
 
-    pub fn to_inner(&self) -> &RwLock<Vec<GenericEntry>> {
-        &self.results
-    }
-
     pub fn should_stop(&self) -> bool {
         self.should_stop.load(Ordering::Relaxed)
     }
 
+    /// the plugin that claimed the query with [`ResultBuilderRef::claim_exclusive`]
+    pub fn exclusive_plugin(&self) -> Option<usize> {
+        Some(self.exclusive.load(Ordering::Relaxed)).filter(|v| *v != usize::MAX)
+    }
+
     pub fn get_should_stop(&self) -> Arc<AtomicBool> {
         self.should_stop.clone()
     }
@@ -177,8 +328,9 @@ pub fn collector() -> impl Stream<Item = CollectorMessage> {
                 .build()
                 .expect("failed to run tokio collector runtime");
             rt.block_on(async {
+                let mut last_input = None;
                 loop {
-                    let (plugins, mut query, should_stop, context) = match StreamExt::next(
+                    let (plugins, query, should_stop, context) = match StreamExt::next(
                         &mut receiver,
                     )
                     .await
@@ -195,16 +347,42 @@ pub fn collector() -> impl Stream<Item = CollectorMessage> {
                     };
                     let mut next_message_fn = async || _ = receiver.next().await;
                     let result_builder = ResultBuilder {
-                        results: RwLock::default(),
                         should_stop,
+                        ..Default::default()
                     };
 
                     let settings_ref = context.config.plugin_settings.as_ref_async().await;
+                    // an empty query shows the favorites, which are looked up by querying their
+                    // plugin for their name, followed by the recent searches
+                    let favorites = query.is_empty().then(|| favorite_ids(&plugins, &context));
+                    let history = plugins
+                        .iter()
+                        .position(|plugin| plugin.any_prefix() == HistoryPlugin::prefix());
                     let mut futures = 'block: {
+                        if let Some(favorites) = &favorites {
+                            let recent = history.map(|id| (id, String::new(), false));
+                            break 'block favorites
+                                .iter()
+                                .map(|(id, name)| (*id, name.to_lowercase(), true))
+                                .chain(recent)
+                                .map(|(id, query, has_prefix)| {
+                                    let plugin = &plugins[id];
+                                    plugin.any_get_for_values(
+                                        Arc::new(MatcherInput::new(query, has_prefix)),
+                                        &result_builder,
+                                        id,
+                                        PluginContext::from_context(
+                                            &context,
+                                            settings_ref.get_root(plugin.any_prefix()),
+                                        ),
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                        }
+
                         for (id, plugin) in plugins.iter().enumerate() {
-                            if query.starts_with(plugin.any_prefix()) {
-                                query.drain(..plugin.any_prefix().len());
-                                let input = Arc::new(MatcherInput::new(query, true));
+                            if let Some(query) = plugin_query(&query, plugin.any_prefix()) {
+                                let input = matcher_input(&mut last_input, query, true);
                                 break 'block vec![plugin.any_get_for_values(
                                     input,
                                     &result_builder,
@@ -217,7 +395,7 @@ pub fn collector() -> impl Stream<Item = CollectorMessage> {
                             }
                         }
 
-                        let input = Arc::new(MatcherInput::new(query, false));
+                        let input = matcher_input(&mut last_input, &query, false);
                         plugins
                             .iter()
                             .enumerate()
@@ -248,26 +426,31 @@ pub fn collector() -> impl Stream<Item = CollectorMessage> {
                             JoinAllResult::Abort => break,
                             JoinAllResult::Done(moved_futures) => futures = moved_futures,
                         }
-                        let mut writer = result_builder.to_inner().write().await;
-                        if writer.len() == sent_previously {
+                        let mut writer = result_builder.results.write().await;
+                        if writer.added == sent_previously {
                             continue;
                         }
-                        sent_previously = writer.len();
+                        sent_previously = writer.added;
                         let mut entries = if futures.is_empty() {
-                            std::mem::take(&mut *writer)
+                            std::mem::take(&mut *writer).sorted()
                         } else {
-                            writer.clone()
+                            writer.to_sorted()
                         };
                         drop(writer);
-                        entries.sort_by(|a, b| {
-                            if a.perfect_match == b.perfect_match {
-                                cmp::Ordering::Equal
-                            } else if a.perfect_match {
-                                cmp::Ordering::Less
-                            } else {
-                                cmp::Ordering::Greater
+                        if let Some(plugin) = result_builder.exclusive_plugin() {
+                            entries.retain(|entry| entry.plugin == plugin);
+                        }
+                        if let Some(favorites) = &favorites {
+                            keep_favorites(&mut entries, favorites, history);
+                            let res = output.send(CollectorMessage::Finished(entries)).await;
+                            if handle_send_result(res) {
+                                return;
                             }
-                        });
+                            continue;
+                        }
+                        if context.config.group_results {
+                            group_by_plugin(&mut entries);
+                        }
                         let res = output.send(CollectorMessage::Finished(entries)).await;
                         if handle_send_result(res) {
                             return;
@@ -280,6 +463,88 @@ pub fn collector() -> impl Stream<Item = CollectorMessage> {
     })
 }
 
+/// the plugin index and name of every configured favorite whose plugin is loaded
+fn favorite_ids(plugins: &[Arc<dyn AnyPlugin>], context: &Context) -> Vec<(usize, String)> {
+    context
+        .config
+        .favorites
+        .iter()
+        .filter_map(|favorite| {
+            let id = plugins
+                .iter()
+                .position(|plugin| plugin.any_prefix() == favorite.plugin)?;
+            Some((id, favorite.name.clone()))
+        })
+        .collect()
+}
+
+/// removes everything that isn't a favorite or from the history plugin and orders the favorites
+/// like the config does, followed by the recent searches. if a plugin returns multiple entries
+/// with the name of a favorite, only the first one is kept.
+fn keep_favorites(
+    entries: &mut Vec<GenericEntry>,
+    favorites: &[(usize, String)],
+    history: Option<usize>,
+) {
+    let mut found = vec![false; favorites.len()];
+    entries.retain(|entry| {
+        let Some(pos) = favorites
+            .iter()
+            .position(|(id, name)| *id == entry.plugin && *name == entry.name)
+        else {
+            return Some(entry.plugin) == history;
+        };
+        !std::mem::replace(&mut found[pos], true)
+    });
+    // the sort is stable, so the recent searches stay in their order
+    entries.sort_by_key(|entry| {
+        favorites
+            .iter()
+            .position(|(id, name)| *id == entry.plugin && *name == entry.name)
+            .unwrap_or(favorites.len())
+    });
+}
+
+/// moves the results of each plugin next to each other. the groups are ordered by their best
+/// result and keep the order within them.
+fn group_by_plugin(entries: &mut [GenericEntry]) {
+    let mut order = HashMap::new();
+    for entry in entries.iter() {
+        let next = order.len();
+        order.entry(entry.plugin).or_insert(next);
+    }
+    entries.sort_by_key(|entry| order[&entry.plugin]);
+}
+
+/// the query without the prefix if it starts with it. the whitespace between the prefix and the
+/// rest is removed as well, so plugins never get leading whitespace, and a query that is only the
+/// prefix is empty. the prefix can be typed in any case.
+fn plugin_query<'a>(query: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = query.get(..prefix.len())?;
+    start
+        .eq_ignore_ascii_case(prefix)
+        .then(|| query[prefix.len()..].trim_start())
+}
+
+/// reuses the input of the last query if no plugin is holding onto it anymore, which avoids
+/// reallocating the words on every keystroke.
+fn matcher_input(
+    last_input: &mut Option<Arc<MatcherInput>>,
+    query: &str,
+    has_prefix: bool,
+) -> Arc<MatcherInput> {
+    let input = if let Some(mut input) = last_input.take()
+        && let Some(inner) = Arc::get_mut(&mut input)
+    {
+        inner.set_input(query, has_prefix);
+        input
+    } else {
+        Arc::new(MatcherInput::new(query.to_string(), has_prefix))
+    };
+    *last_input = Some(input.clone());
+    input
+}
+
 fn handle_send_result(res: Result<(), SendError>) -> bool {
     match res {
         Ok(()) => false,
@@ -323,3 +588,80 @@ impl<'a, Eeper: Future, F: Future + Unpin> Future for Joinall<'a, '_, Eeper, F>
         }
     }
 }
+
+/// runs a single plugin for a query and returns everything it added, for tests. the plugin gets
+/// the query as if its prefix was typed if `has_prefix` is set.
+#[cfg(test)]
+pub fn collect_entries(
+    plugin: &dyn AnyPlugin,
+    query: &str,
+    has_prefix: bool,
+    context: &Context,
+) -> Vec<GenericEntry> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("a runtime can be built");
+    let builder = ResultBuilder::default();
+    let settings = context.config.plugin_settings.as_ref();
+    let context = PluginContext::from_context(context, settings.get_root(plugin.any_prefix()));
+    let input = Arc::new(MatcherInput::new(query.to_lowercase(), has_prefix));
+    runtime.block_on(plugin.any_get_for_values(input, &builder, 0, context));
+    builder.results.into_inner().sorted()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MAX_RESULTS, Results, collect_entries, plugin_query};
+    use crate::{
+        AnyPlugin, Context, CustomData, GenericEntry, control_plugin::ControlPlugin,
+        date_plugin::DatePlugin, dice_plugin::DicePlugin, fend_plugin::FendPlugin,
+        theme_plugin::ThemePlugin,
+    };
+
+    #[test]
+    fn prefix_is_removed() {
+        assert_eq!(plugin_query("fend 1 + 1", "fend"), Some("1 + 1"));
+        assert_eq!(plugin_query("fend   1 + 1", "fend"), Some("1 + 1"));
+        assert_eq!(plugin_query("fend", "fend"), Some(""));
+        assert_eq!(plugin_query("Fend 1 + 1", "fend"), Some("1 + 1"));
+        assert_eq!(plugin_query("roll 2d6", "fend"), None);
+    }
+
+    #[test]
+    fn prefix_only_shows_nothing() {
+        let context = Context::test();
+        let plugins: [&dyn AnyPlugin; 5] = [
+            &ControlPlugin,
+            &DatePlugin,
+            &DicePlugin,
+            &FendPlugin::default(),
+            &ThemePlugin,
+        ];
+        for plugin in plugins {
+            let entries = collect_entries(plugin, "", true, &context);
+            assert!(entries.is_empty(), "{}", plugin.any_prefix());
+        }
+    }
+
+    #[test]
+    fn results_of_claiming_plugin_are_kept() {
+        let entry = |plugin, weight| GenericEntry {
+            weight,
+            ..GenericEntry::new("", "", plugin, CustomData::new(()))
+        };
+        let mut results = Results::default();
+        results.push(entry(1, 0));
+        for weight in 1..=2000 {
+            results.push(entry(0, weight));
+        }
+        let entries = results.to_sorted();
+        assert_eq!(entries.len(), MAX_RESULTS);
+        assert_eq!(entries[0].weight, 2000);
+        assert!(entries.is_sorted_by_key(|v| -v.weight));
+        results.purge(1);
+        let entries = results.sorted();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].plugin, 1);
+    }
+}
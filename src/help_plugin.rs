@@ -0,0 +1,56 @@
+use iced::Task;
+
+use crate::{
+    Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef, matcher::MatcherInput,
+    plugin::{StringLike, StructPlugin},
+};
+
+/// Lists all enabled plugins when searching for `?` or `help`.
+#[derive(Default)]
+pub struct HelpPlugin;
+
+impl StructPlugin for HelpPlugin {
+    fn prefix() -> &'static str {
+        "help"
+    }
+
+    fn description() -> &'static str {
+        "List all enabled plugins and their prefixes"
+    }
+
+    async fn get_for_values(
+        &self,
+        input: &MatcherInput,
+        builder: ResultBuilderRef<'_>,
+        ctx: PluginContext<'_>,
+    ) {
+        if !input.has_prefix() && input.input().trim() != "?" {
+            return;
+        }
+        let show_all = !input.has_prefix() || input.input().trim().is_empty();
+        let iter = ctx
+            .plugins()
+            .filter(|plugin| plugin.enabled && plugin.prefix != Self::prefix())
+            .filter(|plugin| {
+                show_all || input.matches(&plugin.prefix) || input.matches(&plugin.description)
+            })
+            .map(|plugin| {
+                Entry::new(
+                    plugin.prefix.clone(),
+                    plugin.description,
+                    CustomData::new(plugin.prefix),
+                )
+            });
+        builder.commit(iter).await;
+    }
+
+    async fn init(&mut self, _: PluginContext<'_>) {}
+
+    fn handle_pre(&self, thing: CustomData, _: &str, _: PluginContext<'_>) -> Task<Message> {
+        Task::done(Message::SetSearch(format!("{} ", thing.into::<StringLike>())))
+    }
+
+    fn actions(&self) -> &'static [Action] {
+        const { &[Action::default("Use Plugin", "").keep_open()] }
+    }
+}
@@ -0,0 +1,183 @@
+// Plugin to search the queries actions were run from again
+
+use std::{collections::HashMap, sync::Arc};
+
+use iced::{
+    Task,
+    keyboard::{Key, Modifiers, key::Named},
+};
+
+use crate::{
+    Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef, StructPlugin,
+    config::PluginSettings, matcher::MatcherInput, plugin::StringLike, sqlite,
+};
+
+/// how many recent searches are shown when the search is empty
+const EMPTY_QUERY_LIMIT: usize = 8;
+
+#[derive(Default)]
+pub struct HistoryPlugin;
+
+impl StructPlugin for HistoryPlugin {
+    fn prefix() -> &'static str {
+        "history"
+    }
+
+    fn display_name() -> &'static str {
+        "Recent Searches"
+    }
+
+    fn description() -> &'static str {
+        "Run recent searches again. Nothing is recorded while this plugin is disabled"
+    }
+
+    fn config() -> Option<PluginSettings> {
+        let mut values = HashMap::new();
+        values.insert(
+            "show_when_empty".into(),
+            PluginSettings::Toggle {
+                label: Some("Show the recent searches when the search is empty".into()),
+                default: true,
+            },
+        );
+        values.insert(
+            "history_length".into(),
+            PluginSettings::IntInput {
+                min: Some(0),
+                max: None,
+                step: 1,
+                default: 50,
+                label: Some("Amount of searches kept in the history".into()),
+            },
+        );
+        Some(PluginSettings::Object {
+            values,
+            label: Some("Recent Searches".into()),
+        })
+    }
+
+    /// without the prefix, this is only asked for the empty search
+    async fn get_for_values(
+        &self,
+        input: &MatcherInput,
+        builder: ResultBuilderRef<'_>,
+        context: PluginContext<'_>,
+    ) {
+        let show_when_empty = context
+            .config
+            .is_none_or(|v| v["show_when_empty"].as_boolean_default());
+        let limit = if input.has_prefix() {
+            usize::MAX
+        } else if input.is_empty() && show_when_empty {
+            EMPTY_QUERY_LIMIT
+        } else {
+            return;
+        };
+        let history = sqlite::await_query_all(
+            &context.sqlite,
+            "SELECT query FROM query_history ORDER BY id DESC",
+            [].into(),
+            |row| row.get::<_, String>("query"),
+        )
+        .await;
+        let history = match history {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("Failed to read the search history: {e}");
+                return;
+            }
+        };
+        let iter = history
+            .into_iter()
+            .filter(|query| input.is_empty() || input.matches(query))
+            .take(limit)
+            .map(|query| {
+                let query: Arc<str> = query.into();
+                Entry::new(query.clone(), StringLike::Empty, CustomData::new(query))
+            });
+        builder.commit(iter).await;
+    }
+
+    async fn init(&mut self, context: PluginContext<'_>) {
+        if let Err(e) = sqlite::await_execute(
+            &context.sqlite,
+            "CREATE TABLE IF NOT EXISTS query_history(id INTEGER PRIMARY KEY AUTOINCREMENT, query TEXT)",
+            [].into(),
+        )
+        .await
+        {
+            log::error!("Failed to create the search history: {e}");
+        }
+    }
+
+    fn handle_pre(
+        &self,
+        thing: CustomData,
+        action: &str,
+        context: PluginContext<'_>,
+    ) -> Task<Message> {
+        let query = thing.into::<Arc<str>>();
+        match action {
+            "search" => return Task::done(Message::SetSearch(query.to_string())),
+            "forget" => sqlite::execute(
+                &context.sqlite,
+                "DELETE FROM query_history WHERE query = ?1",
+                [Box::new(query) as Box<_>].into(),
+            ),
+            "clear" => sqlite::execute(&context.sqlite, "DELETE FROM query_history", [].into()),
+            _ => return Task::none(),
+        }
+        // shows the history without the removed searches
+        let current = context.query().unwrap_or_default().to_string();
+        Task::done(Message::UpdateSearch(current))
+    }
+
+    fn actions(&self) -> &'static [Action] {
+        const {
+            &[
+                Action::default("Search", "search").keep_open(),
+                Action::new(
+                    "Remove from history",
+                    "forget",
+                    (Modifiers::CTRL, Key::Named(Named::Delete)),
+                )
+                .keep_open(),
+                Action::without_shortcut("Clear history", "clear").keep_open(),
+            ]
+        }
+    }
+}
+
+/// remembers the query an action was run from. the query moves to the top if it's already in the
+/// history.
+pub fn record(context: &PluginContext<'_>, query: &str) {
+    let query = query.trim();
+    // searching the history again isn't worth remembering
+    let first_word = query.split_whitespace().next();
+    if first_word.is_none_or(|v| v.eq_ignore_ascii_case(HistoryPlugin::prefix())) {
+        return;
+    }
+    let max_len = context
+        .config
+        .map_or(50, |v| v["history_length"].as_int_default());
+    // a length of 0 keeps nothing, including what was recorded before it was set
+    if max_len <= 0 {
+        sqlite::execute(&context.sqlite, "DELETE FROM query_history", [].into());
+        return;
+    }
+    let statements = vec![
+        (
+            "DELETE FROM query_history WHERE query = ?1".into(),
+            [Box::new(query.to_string()) as Box<_>].into(),
+        ),
+        (
+            "INSERT INTO query_history (query) VALUES (?1)".into(),
+            [Box::new(query.to_string()) as Box<_>].into(),
+        ),
+        (
+            "DELETE FROM query_history WHERE id NOT IN (SELECT id FROM query_history ORDER BY id DESC LIMIT ?1)".into(),
+            [Box::new(max_len) as Box<_>].into(),
+        ),
+    ];
+    sqlite::execute_all(&context.sqlite, statements);
+}
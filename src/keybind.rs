@@ -3,6 +3,8 @@ use std::{collections::HashMap, sync::LazyLock};
 
 use iced::keyboard::{Key, Modifiers, key::Named};
 
+use crate::ALLOWED_ACTION_MODIFIERS;
+
 static NAMED_KEY: LazyLock<HashMap<&'static str, Named>> = LazyLock::new(|| {
     let mut map = HashMap::new();
     map.insert("alt", Named::Alt);
@@ -24,6 +26,8 @@ static NAMED_KEY: LazyLock<HashMap<&'static str, Named>> = LazyLock::new(|| {
     map.insert("cmd", Named::Super);
     map.insert("command", Named::Super);
     map.insert("enter", Named::Enter);
+    map.insert("esc", Named::Escape);
+    map.insert("escape", Named::Escape);
     map.insert("tab", Named::Tab);
     map.insert("space", Named::Space);
     map.insert("down", Named::ArrowDown);
@@ -55,6 +59,7 @@ static NAMED_KEY: LazyLock<HashMap<&'static str, Named>> = LazyLock::new(|| {
     map.insert("new", Named::New);
     map.insert("open", Named::Open);
     map.insert("print", Named::Print);
+    map.insert("printscreen", Named::PrintScreen);
     map.insert("save", Named::Save);
     map.insert("f1", Named::F1);
     map.insert("f2", Named::F2);
@@ -136,6 +141,14 @@ pub fn key_and_modifiers_from_str(s: &str) -> Option<(Modifiers, Key)> {
     }
 }
 
+/// if a key press with these modifiers is forwarded to the actions as a shortcut. shift on its own
+/// is used to type characters, so it only counts together with another modifier (e.g. ctrl + shift).
+pub fn is_shortcut_modifiers(modifiers: Modifiers) -> bool {
+    ALLOWED_ACTION_MODIFIERS
+        .difference(Modifiers::SHIFT)
+        .intersects(modifiers)
+}
+
 pub fn iced_key_to_code(key: Key) -> Option<Code> {
     match key {
         Key::Named(Named::CapsLock) => Some(Code::CapsLock),
@@ -144,6 +157,8 @@ pub fn iced_key_to_code(key: Key) -> Option<Code> {
         Key::Named(Named::NumLock) => Some(Code::NumLock),
         Key::Named(Named::ScrollLock) => Some(Code::ScrollLock),
         Key::Named(Named::Enter) => Some(Code::Enter),
+        Key::Named(Named::Escape) => Some(Code::Escape),
+        Key::Named(Named::PrintScreen) => Some(Code::PrintScreen),
         Key::Named(Named::Tab) => Some(Code::Tab),
         Key::Named(Named::Space) => Some(Code::Space),
         Key::Named(Named::ArrowDown) => Some(Code::ArrowDown),
@@ -203,8 +218,8 @@ pub fn iced_key_to_code(key: Key) -> Option<Code> {
         Key::Character(c) => match c.as_str() {
             "`" => Some(Code::Backquote),
             "\\" => Some(Code::Backslash),
-            "(" => Some(Code::BracketLeft),
-            ")" => Some(Code::BracketRight),
+            "[" => Some(Code::BracketLeft),
+            "]" => Some(Code::BracketRight),
             "," => Some(Code::Comma),
             "0" => Some(Code::Digit0),
             "1" => Some(Code::Digit1),
@@ -245,7 +260,7 @@ pub fn iced_key_to_code(key: Key) -> Option<Code> {
             "Z" | "z" => Some(Code::KeyZ),
             "-" => Some(Code::Minus),
             "." => Some(Code::Period),
-            "\"" => Some(Code::Quote),
+            "'" => Some(Code::Quote),
             ";" => Some(Code::Semicolon),
             "/" => Some(Code::Slash),
             _ => None,
@@ -270,3 +285,93 @@ pub fn iced_to_hotkey(keybind: (Modifiers, Key)) -> Option<HotKey> {
     }
     Some(HotKey::new(Some(mods), iced_key_to_code(keybind.1)?))
 }
+
+#[cfg(test)]
+mod test {
+    use global_hotkey::hotkey::{Code, HotKey, Modifiers as HKModifiers};
+    use iced::keyboard::{Key, Modifiers, key::Named};
+
+    use crate::{
+        format_key,
+        keybind::{
+            iced_key_to_code, iced_to_hotkey, is_shortcut_modifiers, key_and_modifiers_from_str,
+        },
+    };
+
+    fn round_trip(keybind: &str) -> (Modifiers, Key) {
+        let parsed = key_and_modifiers_from_str(keybind).expect("failed to parse the keybind");
+        let mut formatted = String::new();
+        format_key(&parsed.1, parsed.0, &mut formatted);
+        assert_eq!(
+            key_and_modifiers_from_str(&formatted),
+            Some(parsed.clone()),
+            "{keybind:?} was formatted as {formatted:?}"
+        );
+        parsed
+    }
+
+    #[test]
+    fn parse_format_parse() {
+        assert_eq!(
+            round_trip("Ctrl+Space"),
+            (Modifiers::CTRL, Key::Named(Named::Space))
+        );
+        assert_eq!(
+            round_trip("Alt+P"),
+            (Modifiers::ALT, Key::Character("p".into()))
+        );
+        assert_eq!(
+            round_trip("Super+F1"),
+            (Modifiers::LOGO, Key::Named(Named::F1))
+        );
+        assert_eq!(
+            round_trip("ctrl + alt + enter"),
+            (Modifiers::CTRL | Modifiers::ALT, Key::Named(Named::Enter))
+        );
+    }
+
+    #[test]
+    fn hotkey() {
+        let hotkey = key_and_modifiers_from_str("Ctrl+Space").and_then(iced_to_hotkey);
+        assert_eq!(
+            hotkey,
+            Some(HotKey::new(Some(HKModifiers::CONTROL), Code::Space))
+        );
+        let hotkey = key_and_modifiers_from_str("Super+F1").and_then(iced_to_hotkey);
+        assert_eq!(hotkey, Some(HotKey::new(Some(HKModifiers::SUPER), Code::F1)));
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(
+            iced_key_to_code(Key::Character("[".into())),
+            Some(Code::BracketLeft)
+        );
+        assert_eq!(
+            iced_key_to_code(Key::Character("]".into())),
+            Some(Code::BracketRight)
+        );
+        assert_eq!(iced_key_to_code(Key::Character("(".into())), None);
+        assert_eq!(iced_key_to_code(Key::Character(")".into())), None);
+        assert_eq!(
+            iced_key_to_code(Key::Character("'".into())),
+            Some(Code::Quote)
+        );
+        assert_eq!(iced_key_to_code(Key::Character("\"".into())), None);
+        assert_eq!(
+            key_and_modifiers_from_str("Ctrl+Esc")
+                .and_then(|(_, key)| iced_key_to_code(key)),
+            Some(Code::Escape)
+        );
+    }
+
+    #[test]
+    fn shortcut_modifiers() {
+        assert!(!is_shortcut_modifiers(Modifiers::empty()));
+        assert!(!is_shortcut_modifiers(Modifiers::SHIFT));
+        assert!(is_shortcut_modifiers(Modifiers::CTRL));
+        assert!(is_shortcut_modifiers(Modifiers::CTRL | Modifiers::SHIFT));
+        assert!(is_shortcut_modifiers(Modifiers::ALT | Modifiers::SHIFT));
+        assert!(is_shortcut_modifiers(Modifiers::LOGO | Modifiers::SHIFT));
+    }
+}
@@ -1,15 +1,19 @@
 use std::{
-    fs::OpenOptions,
-    path::PathBuf,
+    fmt::Arguments,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
     process::Command,
-    sync::{LazyLock, OnceLock, RwLock},
+    sync::{LazyLock, Mutex, OnceLock, RwLock},
 };
 
+use env_filter::Filter;
 use env_logger::{Target, WriteStyle};
 use log::{Level, LevelFilter, Log, Metadata, Record};
 
 use crate::{
     Message,
+    config::{Config, LogLevel, NotificationLevel},
     special_windows::SpecialWindowState,
     utils::{self, CRATE_NAME},
 };
@@ -21,69 +25,230 @@ pub struct Logger {
 
 #[allow(clippy::type_complexity)]
 static SENDER: OnceLock<RwLock<Box<dyn Send + Sync + FnMut(Message)>>> = OnceLock::new();
+static NOTIFICATIONS: RwLock<NotificationConfig> = RwLock::new(NotificationConfig {
+    level: Level::Warn,
+    errors_as_notifications: false,
+});
+/// decides what's written to the log, replaced when the config changes
+static FILTER: LazyLock<RwLock<Filter>> =
+    LazyLock::new(|| RwLock::new(build_filter(LevelFilter::Debug, [])));
 pub static LOG_FILE: LazyLock<PathBuf> = LazyLock::new(|| utils::DATA_DIR.join("latest.log"));
+/// the open log file, which is replaced when it's rotated
+static FILE: Mutex<Option<File>> = Mutex::new(None);
 
-pub fn register_message_sender(sender: impl FnMut(Message) + Send + Sync + 'static) {
+/// popups that were logged before the sender was registered, e.g. while loading the config at
+/// startup
+static PENDING: Mutex<Vec<Message>> = Mutex::new(Vec::new());
+
+pub fn register_message_sender(mut sender: impl FnMut(Message) + Send + Sync + 'static) {
+    let mut pending = PENDING.lock().expect("failed to lock");
+    for message in pending.drain(..) {
+        sender(message);
+    }
     SENDER
         .set(RwLock::new(Box::new(sender)))
         .ok()
         .expect("sender is already set");
 }
 
-pub fn init() {
-    let stderr_logger = env_logger::Builder::new()
-        .filter_level(LevelFilter::Debug)
+/// sends `message` to the app, or queues it until the sender is registered
+fn send(message: Message) {
+    let mut pending = PENDING.lock().expect("failed to lock");
+    match SENDER.get() {
+        Some(sender) => (sender.write().expect("failed to write"))(message),
+        None => pending.push(message),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct NotificationConfig {
+    level: Level,
+    errors_as_notifications: bool,
+}
+
+/// applies the log level and notification settings of the config. until this is called, debug
+/// logs are written, info logs don't send notifications and warnings and errors open popups.
+pub fn apply_config(config: &Config) {
+    let filter = build_filter(
+        level_filter(config.log_level),
+        config
+            .log_modules
+            .iter()
+            .map(|(module, level)| (module.as_str(), level_filter(*level))),
+    );
+    set_max_level(&filter);
+    *FILTER.write().expect("failed to write") = filter;
+    rotate(config.log_max_size * 1024, config.log_rotations);
+    *NOTIFICATIONS.write().expect("failed to write") = NotificationConfig {
+        level: match config.notification_level() {
+            NotificationLevel::Error => Level::Error,
+            NotificationLevel::Warn => Level::Warn,
+            NotificationLevel::Info => Level::Info,
+        },
+        errors_as_notifications: config.errors_as_notifications,
+    };
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Off => LevelFilter::Off,
+        LogLevel::Error => LevelFilter::Error,
+        LogLevel::Warn => LevelFilter::Warn,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Trace => LevelFilter::Trace,
+    }
+}
+
+/// the filter for `level`, quieting down some noisy dependencies. `modules` and `RUST_LOG`
+/// override it.
+fn build_filter<'a>(
+    level: LevelFilter,
+    modules: impl IntoIterator<Item = (&'a str, LevelFilter)>,
+) -> Filter {
+    let mut builder = env_filter::Builder::new();
+    builder
+        .filter_level(level)
         .filter_module("wgpu_hal", LevelFilter::Error)
         .filter_module("wgpu_core", LevelFilter::Info)
         .filter_module("naga", LevelFilter::Info)
         .filter_module("cosmic_text", LevelFilter::Info)
         .filter_module("iced_winit", LevelFilter::Warn)
-        .filter_module("iced_wgpu", LevelFilter::Warn)
-        .parse_default_env()
-        .build();
-    println!("Trying to create log file '{}'", LOG_FILE.display());
-    std::fs::create_dir_all(LOG_FILE.parent().expect("this has to be true")).unwrap();
-    let file = OpenOptions::new()
+        .filter_module("iced_wgpu", LevelFilter::Warn);
+    for (module, level) in modules {
+        builder.filter_module(module, level);
+    }
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse(&filters);
+    }
+    builder.build()
+}
+
+/// info logs are always let through, as they can send notifications
+fn set_max_level(filter: &Filter) {
+    log::set_max_level(filter.filter().max(LevelFilter::Info));
+}
+
+fn open_log_file() -> io::Result<File> {
+    OpenOptions::new()
         .append(true)
         .create(true)
         .open(&*LOG_FILE)
-        .unwrap();
+}
+
+/// the path of the `n`th old log file
+fn rotated_log_file(log_file: &Path, n: u32) -> PathBuf {
+    let mut path = log_file.as_os_str().to_owned();
+    path.push(format!(".{n}"));
+    path.into()
+}
+
+/// removes the old log files after the first `keep`, e.g. the ones left over from before
+/// `log_rotations` was lowered
+fn prune_rotated(log_file: &Path, keep: u32) {
+    for n in keep.saturating_add(1)..=u32::MAX {
+        match std::fs::remove_file(rotated_log_file(log_file, n)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+            Err(e) => {
+                log::error!("failed to remove an old log file: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// moves the log file to `latest.log.1` and the older ones up by one if it's larger than
+/// `max_size` bytes, keeping `rotations` of them.
+fn rotate(max_size: u64, rotations: u32) {
+    prune_rotated(&LOG_FILE, rotations);
+    if max_size == 0 {
+        return;
+    }
+    // nothing can be logged while the file is locked, so errors are logged once it's unlocked
+    let result = {
+        let mut file = FILE.lock().expect("failed to lock");
+        match file.as_ref().map(File::metadata) {
+            Some(Ok(metadata)) if metadata.len() > max_size => {
+                for n in (1..rotations).rev() {
+                    _ = std::fs::rename(
+                        rotated_log_file(&LOG_FILE, n),
+                        rotated_log_file(&LOG_FILE, n + 1),
+                    );
+                }
+                let result = if rotations == 0 {
+                    std::fs::remove_file(&*LOG_FILE)
+                } else {
+                    std::fs::rename(&*LOG_FILE, rotated_log_file(&LOG_FILE, 1))
+                };
+                result.and_then(|()| {
+                    *file = Some(open_log_file()?);
+                    Ok(())
+                })
+            }
+            Some(Err(e)) => Err(e),
+            _ => Ok(()),
+        }
+    };
+    if let Err(e) = result {
+        log::error!("failed to rotate the log file: {e}");
+    }
+}
+
+/// writes to [`FILE`]
+struct LogFileWriter;
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut *FILE.lock().expect("failed to lock") {
+            Some(file) => file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut *FILE.lock().expect("failed to lock") {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn notify(urgency: &str, message: &Arguments<'_>) {
+    let mut cmd = Command::new("notify-send");
+    cmd.arg("-u").arg(urgency).arg(message.to_string());
+    utils::run_cmd(cmd);
+}
+
+pub fn init() {
+    // the loggers let everything through, the filtering is done by `FILTER`
+    let stderr_logger = env_logger::Builder::new()
+        .filter_level(LevelFilter::Trace)
+        .build();
+    println!("Trying to create log file '{}'", LOG_FILE.display());
+    std::fs::create_dir_all(LOG_FILE.parent().expect("this has to be true")).unwrap();
+    *FILE.lock().expect("failed to lock") = Some(open_log_file().unwrap());
     let file_logger = env_logger::Builder::new()
-        .filter_level(LevelFilter::Debug)
-        .filter_module("wgpu_hal", LevelFilter::Error)
-        .filter_module("wgpu_core", LevelFilter::Info)
-        .filter_module("naga", LevelFilter::Info)
-        .filter_module("cosmic_text", LevelFilter::Info)
-        .filter_module("iced_winit", LevelFilter::Warn)
-        .filter_module("iced_wgpu", LevelFilter::Warn)
-        .target(Target::Pipe(Box::new(file)))
-        .parse_default_env()
+        .filter_level(LevelFilter::Trace)
+        .target(Target::Pipe(Box::new(LogFileWriter)))
         .write_style(WriteStyle::Never)
         .build();
-    let max_level = stderr_logger
-        .filter()
-        .max(file_logger.filter())
-        .max(LevelFilter::Debug);
     let logger = Logger {
         stderr: stderr_logger,
         file: file_logger,
     };
     log::set_boxed_logger(Box::new(logger)).expect("failed to setup the logger");
-    log::set_max_level(max_level);
+    set_max_level(&FILTER.read().expect("failed to read"));
 }
 
 impl Log for Logger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        self.stderr.enabled(metadata)
-            || self.file.enabled(metadata)
-            || metadata.level() <= Level::Info
+        FILTER.read().expect("failed to read").enabled(metadata) || metadata.level() <= Level::Info
     }
 
     fn log(&self, record: &Record) {
-        if self.stderr.enabled(record.metadata()) {
+        if FILTER.read().expect("failed to read").matches(record) {
             self.stderr.log(record);
-        }
-        if self.file.enabled(record.metadata()) {
             self.file.log(record);
         }
         let fmt = record.args();
@@ -94,24 +259,22 @@ impl Log for Logger {
         if !path.starts_with(CRATE_NAME) {
             return;
         }
+        let notifications = *NOTIFICATIONS.read().expect("failed to read");
+        if record.level() > notifications.level {
+            return;
+        }
         match record.level() {
-            Level::Error => {
-                let Some(sender) = SENDER.get() else { return };
-                (sender.write().expect("failed to write"))(Message::OpenSpecial(
-                    SpecialWindowState::new_error_popup(format!("{fmt}")),
-                ));
+            Level::Error if notifications.errors_as_notifications => {
+                notify("critical", fmt);
             }
-            Level::Warn => {
-                let Some(sender) = SENDER.get() else { return };
-                (sender.write().expect("failed to write"))(Message::OpenSpecial(
-                    SpecialWindowState::new_warning_popup(format!("{fmt}")),
-                ));
-            }
-            Level::Info => {
-                let mut cmd = Command::new("notify-send");
-                cmd.arg(format!("{fmt}"));
-                utils::run_cmd(cmd);
+            Level::Warn if notifications.errors_as_notifications => {
+                notify("normal", fmt);
             }
+            Level::Error => send(Message::ShowError(format!("{fmt}"))),
+            Level::Warn => send(Message::OpenSpecial(SpecialWindowState::new_warning_popup(
+                format!("{fmt}"),
+            ))),
+            Level::Info => notify("low", fmt),
             _ => {}
         }
     }
@@ -127,3 +290,24 @@ impl Drop for Logger {
         self.flush();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{prune_rotated, rotated_log_file};
+
+    #[test]
+    fn old_logs_are_pruned() {
+        let dir = std::env::temp_dir().join(format!("luma-logs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_file = dir.join("latest.log");
+        for n in 1..=5 {
+            std::fs::write(rotated_log_file(&log_file, n), "").unwrap();
+        }
+        prune_rotated(&log_file, 2);
+        let kept = (1..=5)
+            .filter(|&n| rotated_log_file(&log_file, n).exists())
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(kept, [1, 2]);
+    }
+}
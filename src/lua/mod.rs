@@ -1,7 +1,11 @@
 use std::{
     collections::HashMap,
-    path::PathBuf,
-    sync::{Arc, LazyLock},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
 };
 
 use iced::{
@@ -16,8 +20,12 @@ use mlua::{
 };
 
 use crate::{
-    Action, CustomData, Entry, Message, Plugin, PluginContext, config::PluginSettings,
-    filter_service::ResultBuilderRef, matcher::MatcherInput, plugin::InstancePlugin,
+    Action, CustomData, Entry, Message, MessageSender, Plugin, PluginContext,
+    config::{LuaCapability, PluginSettings, PluginSettingsValue},
+    filter_service::ResultBuilderRef,
+    matcher::{MatchMode, MatcherInput},
+    plugin::InstancePlugin,
+    utils,
 };
 
 pub struct LuaEntry {
@@ -25,6 +33,9 @@ pub struct LuaEntry {
     subtitle: String,
     data: Value,
     perfect_match: bool,
+    weight: i32,
+    /// hides the results of the other plugins, see [`ResultBuilderRef::claim_exclusive`]
+    exclusive: bool,
 }
 
 impl FromLua for LuaEntry {
@@ -35,6 +46,8 @@ impl FromLua for LuaEntry {
             subtitle: table.get::<Option<String>>("subtitle")?.unwrap_or_default(),
             data: table.get("data")?,
             perfect_match: table.get::<Option<bool>>("perfect_match")?.unwrap_or(false),
+            weight: table.get::<Option<i32>>("weight")?.unwrap_or(0),
+            exclusive: table.get::<Option<bool>>("exclusive")?.unwrap_or(false),
         })
     }
 }
@@ -43,10 +56,13 @@ pub struct LuaPlugin {
     actions: Arc<[Action]>,
     config: Option<PluginSettings>,
     prefix: Arc<str>,
+    display_name: Option<Arc<str>>,
+    description: Arc<str>,
     get_for_values: Function,
     init: Option<Function>,
     handle_pre: Option<Function>,
     handle_post: Option<Function>,
+    on_config_change: Option<Function>,
     table: Table,
     lua: Lua,
 }
@@ -57,10 +73,13 @@ impl Clone for LuaPlugin {
             actions: self.actions.clone(),
             config: None,
             prefix: self.prefix.clone(),
+            display_name: self.display_name.clone(),
+            description: self.description.clone(),
             get_for_values: self.get_for_values.clone(),
             init: self.init.clone(),
             handle_pre: self.handle_pre.clone(),
             handle_post: self.handle_post.clone(),
+            on_config_change: self.on_config_change.clone(),
             table: self.table.clone(),
             lua: self.lua.clone(),
         }
@@ -70,11 +89,7 @@ impl Clone for LuaPlugin {
 impl LuaPlugin {
     fn from_lua(value: Value, lua: &Lua, prefix: impl Into<Arc<str>>) -> mlua::Result<Self> {
         let table: Table = FromLua::from_lua(value, lua)?;
-        let actions_data: Vec<AnyUserData> = table.get("actions")?;
-        let mut actions = Vec::with_capacity(actions_data.len());
-        for action in actions_data {
-            actions.push(action.take()?);
-        }
+        let actions = read_actions(&table)?;
         let prefix = prefix.into();
         let config = table.get::<Option<Table>>("config")?.map(|table| {
             let mut values = HashMap::new();
@@ -94,8 +109,16 @@ impl LuaPlugin {
             init: table.get("init")?,
             handle_pre: table.get("handle_pre")?,
             handle_post: table.get("handle_post")?,
+            on_config_change: table.get("on_config_change")?,
+            display_name: table
+                .get::<Option<String>>("display_name")?
+                .map(Into::into),
+            description: table
+                .get::<Option<String>>("description")?
+                .unwrap_or_default()
+                .into(),
             config,
-            actions: actions.into(),
+            actions,
             prefix,
             table,
             lua: lua.clone(),
@@ -113,7 +136,7 @@ impl LuaPlugin {
             .into_async::<Option<LuaEntry>>((
                 &self.table,
                 MatcherInputUserData(input),
-                ContextUserData::new(context, &self.lua),
+                ContextUserData::new(context, &self.lua, &self.prefix),
             ));
         thread
             .filter_map(async |v| match v {
@@ -127,16 +150,47 @@ impl LuaPlugin {
                 }
             })
             .for_each(|v| async move {
+                if v.exclusive {
+                    builder.claim_exclusive();
+                }
                 builder
                     .add(
                         Entry::new(v.name, v.subtitle, CustomData::new(v.data))
-                            .perfect(v.perfect_match),
+                            .perfect(v.perfect_match)
+                            .weight(v.weight),
                     )
                     .await;
             })
             .await;
         Ok(())
     }
+
+    /// calls the `on_config_change` hook of the plugin after the config was changed
+    pub fn config_changed(&self, context: PluginContext<'_>) -> Task<Message> {
+        let Some(ref f) = self.on_config_change else {
+            return Task::none();
+        };
+        match f.call::<TaskWrapper>((
+            &self.table,
+            ContextUserData::new(context, &self.lua, &self.prefix),
+        )) {
+            Ok(v) => v.0,
+            Err(e) => {
+                log::error!("In {}.lua: {e}", self.prefix);
+                Task::none()
+            }
+        }
+    }
+}
+
+/// the `actions` of a plugin table. they are copied, so the table can be read again after
+/// `init` changed them.
+fn read_actions(table: &Table) -> mlua::Result<Arc<[Action]>> {
+    table
+        .get::<Vec<AnyUserData>>("actions")?
+        .iter()
+        .map(|action| Ok(Action::clone(&*action.borrow::<Action>()?)))
+        .collect()
 }
 
 impl InstancePlugin for LuaPlugin {
@@ -150,6 +204,14 @@ impl Plugin for LuaPlugin {
         &self.prefix
     }
 
+    fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.prefix)
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
     fn actions(&self) -> &[Action] {
         &self.actions
     }
@@ -174,12 +236,24 @@ impl Plugin for LuaPlugin {
     }
 
     async fn init(&mut self, context: PluginContext<'_>) {
-        if let Some(ref f) = self.init
-            && let Err(e) = f
-                .call_async::<Value>((&self.table, ContextUserData::new(context, &self.lua)))
-                .await
+        let Some(ref f) = self.init else {
+            return;
+        };
+        if let Err(e) = f
+            .call_async::<Value>((
+                &self.table,
+                ContextUserData::new(context, &self.lua, &self.prefix),
+            ))
+            .await
         {
             log::error!("In {}.lua: {e}", self.prefix);
+            return;
+        }
+        // `init` can replace `self.actions`, e.g. to offer different actions depending on the
+        // config
+        match read_actions(&self.table) {
+            Ok(actions) => self.actions = actions,
+            Err(e) => log::error!("In {}.lua: invalid actions: {e}", self.prefix),
         }
     }
 
@@ -195,7 +269,7 @@ impl Plugin for LuaPlugin {
                 &self.table,
                 thing,
                 action,
-                ContextUserData::new(context, &self.lua),
+                ContextUserData::new(context, &self.lua, &self.prefix),
             )) {
                 Err(e) => log::error!("In {}.lua: {e}", self.prefix),
                 Ok(v) => return v.0,
@@ -215,7 +289,7 @@ impl Plugin for LuaPlugin {
                 &self.table,
                 thing,
                 action,
-                ContextUserData::new(context, &self.lua),
+                ContextUserData::new(context, &self.lua, &self.prefix),
             )) {
                 Err(e) => log::error!("In {}.lua: {e}", self.prefix),
                 Ok(v) => return v.0,
@@ -225,24 +299,102 @@ impl Plugin for LuaPlugin {
     }
 }
 
-// TODO: add context
-#[repr(transparent)]
-pub struct ContextUserData(mlua::Value);
+pub struct ContextUserData {
+    config: Value,
+    message_sender: MessageSender,
+    query: Option<Arc<str>>,
+    cancelled: Option<Arc<AtomicBool>>,
+}
 impl ContextUserData {
-    pub fn new(ctx: PluginContext, lua: &Lua) -> Self {
+    pub fn new(ctx: PluginContext, lua: &Lua, plugin: &Arc<str>) -> Self {
         let value = ctx
             .config
             .map(|v| v.get_lua(lua).clone())
             .unwrap_or_default();
-        // TODO: add context
-        drop(ctx);
-        Self(value)
+        // the lua value is cached until the config changes, so the metatable only has to be set
+        // the first time it's used.
+        if let Value::Table(ref table) = value
+            && table.metatable().is_none()
+            && let Err(e) = set_config_metatable(
+                lua,
+                table,
+                plugin.clone(),
+                ctx.message_sender.clone(),
+            )
+        {
+            log::error!("In {plugin}.lua: failed to set up the config: {e}");
+        }
+        Self {
+            config: value,
+            message_sender: ctx.message_sender,
+            query: ctx.query,
+            cancelled: ctx.cancelled,
+        }
     }
 }
 
+/// the message `context:emit(name, value)` sends
+fn message_from_lua(name: &str, value: Option<String>) -> mlua::Result<Message> {
+    let search = || value.ok_or_else(|| mlua::Error::runtime(format!("`{name}` needs a string")));
+    Ok(match name {
+        "show" => Message::Show,
+        "hide" => Message::HideMainWindow,
+        "exit" => Message::Exit,
+        "set_search" => Message::SetSearch(search()?),
+        "update_search" => Message::UpdateSearch(search()?),
+        "remove_prefix" => Message::RemovePrefix,
+        _ => return Err(mlua::Error::runtime(format!("unknown message `{name}`"))),
+    })
+}
+
+/// adds `config:set(key, value)`, which changes a value of the plugin's config and saves it.
+fn set_config_metatable(
+    lua: &Lua,
+    config: &Table,
+    plugin: Arc<str>,
+    message_sender: MessageSender,
+) -> mlua::Result<()> {
+    let methods = lua.create_table()?;
+    methods.set(
+        "set",
+        lua.create_function(move |lua, (config, key, value): (Table, String, Value)| {
+            let settings_value = PluginSettingsValue::from_lua(value.clone(), lua)?;
+            config.raw_set(key.as_str(), value)?;
+            message_sender.try_send(Message::SetPluginConfig {
+                plugin: plugin.clone(),
+                key: key.into(),
+                value: settings_value,
+            });
+            Ok(())
+        })?,
+    )?;
+    let metatable = lua.create_table()?;
+    metatable.raw_set("__index", methods)?;
+    config.set_metatable(Some(metatable));
+    Ok(())
+}
+
 impl UserData for ContextUserData {
     fn add_fields<F: mlua::UserDataFields<Self>>(fields: &mut F) {
-        fields.add_field_method_get("config", |_, me| Ok(me.0.clone()));
+        fields.add_field_method_get("config", |_, me| Ok(me.config.clone()));
+        // the query an action was run for, only set in `handle_pre` and `handle_post`
+        fields.add_field_method_get("query", |_, me| Ok(me.query.as_deref().map(str::to_string)));
+    }
+
+    fn add_methods<M: mlua::UserDataMethods<Self>>(methods: &mut M) {
+        // `context:emit("set_search", "query")` sends a message to luma right away, e.g. while
+        // the results are still being collected
+        methods.add_method("emit", |_, me, (name, value): (String, Option<String>)| {
+            me.message_sender.try_send(message_from_lua(&name, value)?);
+            Ok(())
+        });
+        // long running work in `init` should stop once this is true
+        methods.add_method("is_cancelled", |_, me, ()| {
+            Ok(me
+                .cancelled
+                .as_ref()
+                .is_some_and(|v| v.load(Ordering::Relaxed)))
+        });
     }
 }
 
@@ -259,6 +411,21 @@ impl UserData for MatcherInputUserData {
     }
     fn add_methods<M: mlua::UserDataMethods<Self>>(methods: &mut M) {
         methods.add_method("matches", |_, me, v: String| Ok(me.0.matches(&v)));
+        methods.add_method("matches_with", |_, me, (v, mode): (String, String)| {
+            let mode = match &*mode {
+                "subsequence" => MatchMode::Subsequence,
+                "prefix" => MatchMode::Prefix,
+                "exact" => MatchMode::Exact,
+                _ => {
+                    return Err(mlua::Error::FromLuaConversionError {
+                        from: "string",
+                        to: "MatchMode".into(),
+                        message: Some("Expected subsequence, prefix or exact".into()),
+                    });
+                }
+            };
+            Ok(me.0.matches_with(&v, mode))
+        });
     }
 }
 
@@ -301,6 +468,18 @@ impl UserData for TaskWrapper {
         methods.add_function("chain", |_, (me, other): (TaskWrapper, TaskWrapper)| {
             Ok(TaskWrapper(me.0.chain(other.0)))
         });
+        // `task:then(f)` calls `f` once the task finished and runs the task it returns. unlike
+        // `chain`, the next task is only created when it's needed.
+        methods.add_function("then", |_, (me, f): (TaskWrapper, Function)| {
+            let next = Task::done(()).then(move |()| match f.call::<TaskWrapper>(()) {
+                Ok(v) => v.0,
+                Err(e) => {
+                    log::error!("lua: failed to create the next task: {e}");
+                    Task::none()
+                }
+            });
+            Ok(TaskWrapper(me.0.chain(next)))
+        });
     }
 }
 pub struct KeybindWrapper(Modifiers, Key);
@@ -378,10 +557,22 @@ pub fn luma_module(lua: &Lua) -> mlua::Result<Table> {
     // └───────┘
     let task = lua.create_table()?;
     task.set("none", task_fn(lua, |_, ()| Task::none())?)?;
+    // runs all tasks of a table at once, like returning the table does
+    task.set("batch", task_fn(lua, |_, tasks: TaskWrapper| tasks.0)?)?;
+    // runs the tasks of a list one after the other
+    task.set(
+        "sequence",
+        task_fn(lua, |_, tasks: Vec<TaskWrapper>| {
+            tasks
+                .into_iter()
+                .fold(Task::none(), |task, next| task.chain(next.0))
+        })?,
+    )?;
 
     // messages
     task.set("set_search", message(lua, Message::SetSearch)?)?;
     task.set("update_search", message(lua, Message::UpdateSearch)?)?;
+    task.set("remove_prefix", message(lua, |()| Message::RemovePrefix)?)?;
     task.set("show", message(lua, |()| Message::Show)?)?;
     task.set("hide", message(lua, |()| Message::HideMainWindow)?)?;
     task.set("exit", message(lua, |()| Message::Exit)?)?;
@@ -433,9 +624,53 @@ pub fn luma_module(lua: &Lua) -> mlua::Result<Table> {
     )?;
     root.set("action", action)?;
 
+    // ┌───────┐
+    // │ Async │
+    // └───────┘
+    // `luma.sleep(ms)` yields the calling coroutine, so it only works in `init` and
+    // `get_for_values`. all plugins are polled concurrently by the collector, so sleeping only
+    // delays the results of the plugin that sleeps.
+    root.set(
+        "sleep",
+        lua.create_async_function(|_, ms: u64| async move {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(())
+        })?,
+    )?;
+
+    // ┌──────┐
+    // │ Misc │
+    // └──────┘
+    // `luma.open(target)` opens urls (anything with a `scheme://`) in the browser and paths with
+    // their default application.
+    root.set(
+        "open",
+        lua.create_function(|_, target: String| {
+            if target.is_empty() {
+                return Err(mlua::Error::runtime("luma.open: nothing to open"));
+            }
+            if is_url(&target) {
+                utils::open_link(target);
+            } else {
+                utils::open_file(Path::new(&target));
+            }
+            Ok(())
+        })?,
+    )?;
+
     Ok(root)
 }
 
+/// if `s` starts with a scheme followed by `://`
+fn is_url(s: &str) -> bool {
+    s.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
 pub fn proxy(lua: &Lua, proxied_value: Table) -> mlua::Result<Table> {
     let env = lua.create_table()?;
     let metatable = lua.create_table()?;
@@ -456,13 +691,71 @@ pub fn proxy(lua: &Lua, proxied_value: Table) -> mlua::Result<Table> {
 }
 
 pub fn setup_runtime() -> mlua::Result<Lua> {
+    setup_runtime_with(StdLib::NONE)
+}
+
+/// a runtime with the `extra` libraries on top of the ones every plugin gets
+fn setup_runtime_with(extra: StdLib) -> mlua::Result<Lua> {
     let libs = StdLib::COROUTINE | StdLib::TABLE | StdLib::STRING | StdLib::UTF8 | StdLib::MATH;
-    let lua = Lua::new_with(libs, LuaOptions::new())?;
+    let lua = Lua::new_with(libs | extra, LuaOptions::new())?;
     let luma_module = luma_module(&lua)?;
     lua.globals().set("luma", luma_module)?;
     Ok(lua)
 }
 
+/// the capabilities a plugin asks for in the comments at the top of its file, in a line like
+/// `-- capabilities: io, os`
+fn requested_capabilities(src: &str, prefix: &str) -> Vec<LuaCapability> {
+    let Some(line) = src
+        .lines()
+        .map_while(|line| line.trim().strip_prefix("--"))
+        .find_map(|line| line.trim().strip_prefix("capabilities:"))
+    else {
+        return Vec::new();
+    };
+    line.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| {
+            let capability = LuaCapability::from_name(name);
+            if capability.is_none() {
+                log::warn!("In {prefix}.lua: unknown capability `{name}`");
+            }
+            capability
+        })
+        .collect()
+}
+
+/// loads a plugin file. plugins that ask for capabilities that were granted to them get their
+/// own runtime with the libraries for them, all others share `shared`.
+pub fn load_lua_plugin_file(
+    shared: &Lua,
+    path: &Path,
+    prefix: Arc<str>,
+    granted: &[LuaCapability],
+) -> mlua::Result<LuaPlugin> {
+    let src = std::fs::read_to_string(path).map_err(mlua::Error::external)?;
+    let mut libs = StdLib::NONE;
+    for capability in requested_capabilities(&src, &prefix) {
+        if !granted.contains(&capability) {
+            log::warn!(
+                "{prefix}.lua asks for the `{}` capability, which can be granted in `lua_capabilities`",
+                capability.name()
+            );
+            continue;
+        }
+        libs |= match capability {
+            LuaCapability::Io => StdLib::IO,
+            LuaCapability::Os => StdLib::OS,
+        };
+    }
+    if libs == StdLib::NONE {
+        return load_lua_plugin(shared, path, prefix);
+    }
+    let lua = setup_runtime_with(libs)?;
+    load_lua_plugin(&lua, path, prefix)
+}
+
 pub fn load_lua_plugin<'a>(
     lua: &Lua,
     src: impl AsChunk<'a>,
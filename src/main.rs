@@ -5,34 +5,43 @@
 #![allow(clippy::unreadable_literal)]
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, VecDeque},
     ffi::OsStr,
     fmt::Debug,
     hash::Hash,
-    sync::Arc,
-    time::Duration,
+    panic::AssertUnwindSafe,
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use cache::HTTPCache;
-use config::{BlurAction, Config, PluginSettings};
+use config::{BlurAction, Config, Fallback, PluginSettings, PluginSettingsValue};
 use control_plugin::ControlPlugin;
+use date_plugin::DatePlugin;
 use dice_plugin::DicePlugin;
 use fend_plugin::FendPlugin;
+use help_plugin::HelpPlugin;
+use history_plugin::HistoryPlugin;
 use file_index::{FileIndex, FileIndexMessage, FileIndexResponse};
 use file_plugin::FilePlugin;
 use filter_service::{CollectorController, CollectorMessage, ResultBuilderRef};
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState, hotkey::HotKey};
 use iced::{
-    Border, Color, Element, Length, Point, Size, Subscription, Task, Theme,
+    Border, Color, Element, Length, Point, Size, Subscription, Task, Theme, Vector,
+    advanced::graphics::core::SmolStr,
     alignment::{Horizontal, Vertical},
     border::Radius,
     color,
-    futures::{SinkExt, Stream, channel::mpsc::Sender},
+    futures::{FutureExt, SinkExt, Stream, channel::mpsc::Sender},
     keyboard::{Key, Modifiers, key::Named},
-    mouse::ScrollDelta,
+    mouse::{self, ScrollDelta},
     stream::channel,
     widget::{
-        MouseArea, button, column, container, mouse_area, row, stack, text, text_input,
+        MouseArea, button, column, container, mouse_area, row, stack, svg, text, text_input,
         vertical_space,
     },
     window::{self, Level, Position, Settings},
@@ -40,6 +49,8 @@ use iced::{
 use mlua::Lua;
 use notify::{EventKind, RecursiveMode, Watcher};
 use plugin_settings::PluginSettingsRoot;
+use projects_plugin::ProjectsPlugin;
+use path_plugin::PathPlugin;
 use run_plugin::RunPlugin;
 use search_input::SearchInput;
 use special_windows::{SpecialWindowMessage, SpecialWindowState};
@@ -47,27 +58,39 @@ use sqlite::SqliteContext;
 use theme_plugin::ThemePlugin;
 
 mod cache;
+mod clipboard_watcher;
 mod config;
 mod control_plugin;
+mod date_plugin;
 mod dice_plugin;
 mod fend_plugin;
 mod file_index;
 mod file_plugin;
 mod filter_service;
+mod help_plugin;
+mod history_plugin;
 mod keybind;
 mod logging;
 mod lua;
 mod matcher;
+mod native_plugin;
+mod path_plugin;
 mod plugin;
 mod plugin_settings;
+mod projects_plugin;
 mod run_plugin;
 mod search_input;
 mod special_windows;
 mod sqlite;
 mod theme_plugin;
+#[cfg(target_os = "linux")]
+mod tray;
 mod utils;
 pub use filter_service::ResultBuilder;
-use plugin::{AnyPlugin, GenericEntry, InstancePlugin, StringLike, StructPlugin};
+use plugin::{
+    ALWAYS_ENABLED_PLUGINS, AnyPlugin, GenericEntry, InstancePlugin, PluginInfo, StringLike,
+    StructPlugin,
+};
 pub use plugin::{CustomData, Entry, Plugin};
 use tokio::{
     sync::{
@@ -134,6 +157,14 @@ impl MessageSender {
         let _: Result<_, _> = self.0.read().await.send(message);
     }
 
+    /// like [`MessageSender::send`], but for non-async code. the message is also ignored if the
+    /// channel is being replaced
+    pub fn try_send(&self, message: Message) {
+        if let Ok(sender) = self.0.try_read() {
+            let _: Result<_, _> = sender.send(message);
+        }
+    }
+
     async fn replace(&self, new_sender: UnboundedSender<Message>) {
         *self.0.write().await = new_sender;
     }
@@ -152,7 +183,12 @@ pub struct PluginContext<'cfg> {
     sqlite: SqliteContext,
     message_sender: MessageSender,
     global_config: Arc<Config>,
+    registered_plugins: Arc<[(StringLike, StringLike)]>,
+    clipboard_history: Arc<RwLock<VecDeque<Arc<str>>>>,
+    theme: Theme,
     config: Option<&'cfg PluginSettingsRoot>,
+    query: Option<Arc<str>>,
+    cancelled: Option<Arc<AtomicBool>>,
 }
 
 macro_rules! plugin_ctx_from_ctx {
@@ -177,9 +213,66 @@ impl<'cfg> PluginContext<'cfg> {
             sqlite: context.sqlite.clone(),
             message_sender: context.message_sender.clone(),
             global_config: context.config.clone(),
+            registered_plugins: context.registered_plugins.clone(),
+            clipboard_history: context.clipboard_history.clone(),
+            theme: context.theme.clone(),
+            query: None,
+            cancelled: None,
         }
     }
 
+    #[must_use]
+    pub fn with_query(mut self, query: Arc<str>) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    #[must_use]
+    pub fn with_cancellation(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /// Whether the work this context was given for is no longer needed, e.g. because the window
+    /// was closed while the plugin was initializing.
+    ///
+    /// Long running work in `init` should check this regularly and return early once it's set.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .as_ref()
+            .is_some_and(|v| v.load(Ordering::Relaxed))
+    }
+
+    /// The search query at the time an action was run, without the plugin prefix if it was used.
+    ///
+    /// This is only available in `handle_pre` and `handle_post`.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// The theme of the window, e.g. to pick colors that can be read on its background.
+    ///
+    /// Like the config, this is a snapshot taken when the context was created.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Returns all registered plugins and whether they are enabled.
+    ///
+    /// This can be called from any thread (plugins are queried on the collector thread): both the
+    /// plugin list and the config are immutable snapshots taken when the context was created, so
+    /// changes to the config while a query is running are not reflected here.
+    pub fn plugins(&self) -> impl Iterator<Item = PluginInfo> + '_ {
+        self.registered_plugins
+            .iter()
+            .map(|(prefix, description)| PluginInfo {
+                enabled: ALWAYS_ENABLED_PLUGINS.contains(&prefix.to_str())
+                    || self.global_config.enabled_plugins.contains(prefix.to_str()),
+                prefix: prefix.clone(),
+                description: description.clone(),
+            })
+    }
+
     #[must_use]
     pub fn to_context(self) -> Context {
         Context {
@@ -188,6 +281,9 @@ impl<'cfg> PluginContext<'cfg> {
             sqlite: self.sqlite,
             message_sender: self.message_sender,
             config: self.global_config,
+            registered_plugins: self.registered_plugins,
+            clipboard_history: self.clipboard_history,
+            theme: self.theme,
         }
     }
 }
@@ -199,6 +295,32 @@ pub struct Context {
     sqlite: SqliteContext,
     message_sender: MessageSender,
     config: Arc<Config>,
+    /// the id and description of all plugins that were registered, enabled or not.
+    registered_plugins: Arc<[(StringLike, StringLike)]>,
+    /// the texts that were copied while luma was running, newest first. only filled when
+    /// [`Config::watch_clipboard`] is enabled.
+    clipboard_history: Arc<RwLock<VecDeque<Arc<str>>>>,
+    /// the theme of the window at the time the context was cloned
+    theme: Theme,
+}
+
+#[cfg(test)]
+impl Context {
+    /// a context for plugin tests: the default config, an in-memory database, an empty file
+    /// index and a message sender whose messages go nowhere.
+    pub fn test() -> Self {
+        let config = Arc::new(Config::default());
+        Self {
+            http_cache: Arc::new(HTTPCache::new(&config.http).into()),
+            file_index: Arc::new(RwLock::new(FileIndex::new())),
+            sqlite: sqlite::init_in_memory(),
+            message_sender: MessageSender::new(),
+            config,
+            registered_plugins: Arc::new([]),
+            clipboard_history: Arc::default(),
+            theme: Theme::Dracula,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -214,44 +336,85 @@ impl Debug for SharedAnyPlugin {
 #[derive(Debug, Clone)]
 pub enum Message {
     SpecialWindow(SpecialWindowMessage, window::Id),
+    /// the text in the search bar was edited. this is the text after the prefix chip.
     UpdateSearch(String),
+    /// replaces the whole query, including the prefix
     SetSearch(String),
+    /// removes the prefix shown as a chip in front of the search
+    RemovePrefix,
     AddPlugin(SharedAnyPlugin),
     GoUp,
     GoDown,
     Go10Up,
     Go10Down,
-    Submit,
+    /// sent while the up or down arrow is held down
+    HeldGoUp,
+    HeldGoDown,
+    /// runs the selected action. if the bool is true, the window stays open even if the action
+    /// would normally close it
+    Submit(bool),
     Click(usize),
+    /// the mouse entered the nth visible entry
+    Hover(usize),
+    /// the mouse left the nth visible entry
+    Unhover(usize),
+    CursorMoved(window::Id),
+    /// closes the launcher window. luma keeps running in the background, so the hotkey opens it
+    /// again.
     HideMainWindow,
     Hide(window::Id),
+    /// closes the popup if its timeout passed, otherwise waits for the rest of it
+    PopupTimeout(window::Id),
     Show,
     ChangeTheme(Theme),
     HandleAction {
         plugin: usize,
         data: CustomData,
         action: String,
+        query: Arc<str>,
     },
     None,
     InputPress,
+    /// stops luma entirely, after which the hotkey doesn't work anymore
     Exit,
+    /// opens the settings with the current config
+    OpenSettings,
     CollectorMessage(CollectorMessage),
     ResultsUpdated,
     KeyPressed(Key, Modifiers),
     ShowActions,
+    /// selects the entry and shows its actions (e.g. when right clicking it)
+    ShowActionsFor(usize),
     GetContext(TokioSender<Context>),
     UpdateConfig(Arc<Config>, bool),
+    /// sets a value in the config of a plugin and saves it
+    SetPluginConfig {
+        plugin: Arc<str>,
+        key: Box<str>,
+        value: PluginSettingsValue,
+    },
     HideActions,
     Blurred(window::Id),
+    Resized(window::Id, Size),
     OpenSpecial(SpecialWindowState),
+    /// opens an error popup, or adds the error to a recently opened one
+    ShowError(String),
+    CopyToClipboard(String),
     IndexerMessage(FileIndexResponse),
     HotkeyPressed(GlobalHotKeyEvent),
+    /// desktop files were added, changed or removed
+    ApplicationsChanged,
+    /// the text that was copied, see [`Config::watch_clipboard`]
+    ClipboardChanged(Arc<str>),
 }
 
 type PluginBuilder = Box<dyn FnMut() -> Box<dyn AnyPlugin>>;
 
 pub struct State {
-    hotkey: HotKey,
+    /// the hotkey that opens the window, if it could be registered
+    hotkey: Option<HotKey>,
+    /// the hotkey of [`Config::paste_keybind`], if it is set and could be registered
+    paste_hotkey: Option<HotKey>,
     search_query: String,
     results: Vec<GenericEntry>,
     selected: usize,
@@ -260,24 +423,87 @@ pub struct State {
     window: Option<window::Id>,
     plugins: Vec<Arc<dyn AnyPlugin>>,
     initializing_plugins: Vec<AbortHandle>,
+    /// set when the plugins that are currently initializing are no longer needed
+    init_cancelled: Arc<AtomicBool>,
     plugin_builder: Vec<(StringLike, PluginBuilder)>,
     plugin_configs: HashMap<StringLike, PluginSettings>,
-    theme: Theme,
     index_sender: Option<UnboundedSender<FileIndexMessage>>,
     collector_controller: Option<CollectorController>,
     showing_actions: bool,
     selected_action: usize,
+    /// the height the main window was manually resized to, saved when it's hidden
+    resized_height: Option<f32>,
+    /// the visible entry the mouse is over. it's only selected once the mouse moves, so entries
+    /// appearing below a resting cursor don't change the selection.
+    hovered_entry: Option<usize>,
+    /// when the last error popup was opened, used to rate-limit them
+    last_error_popup: Option<Instant>,
+    /// when popups close themselves. errors added to a popup push this back.
+    popup_deadlines: HashMap<window::Id, Instant>,
     special_windows: BTreeMap<window::Id, SpecialWindowState>,
     lua: Lua,
+    /// the loaded lua plugins, used to notify them about config changes
+    lua_plugins: Vec<lua::LuaPlugin>,
     context: Context,
     manager: Arc<GlobalHotKeyManager>,
+    /// the last action that was run, which can be repeated with [`REPEAT_SHORTCUT`]
+    last_action: Option<LastAction>,
+    /// when the arrow key that is held down was pressed
+    navigation_held_since: Option<Instant>,
+    /// the completions tab is cycling through
+    completions: Option<Completions>,
+    /// the prefix of the plugin the search is in, which is shown as a chip in front of the
+    /// search bar instead of being part of the text. the query always starts with it, followed by
+    /// a space. kept when the window is hidden if [`Config::sticky_prefix`] is enabled.
+    active_prefix: Option<String>,
 }
 
+struct Completions {
+    /// the whole queries, including the prefix of the plugin
+    queries: Vec<String>,
+    /// the completion that is in the search bar
+    index: usize,
+}
+
+struct LastAction {
+    /// the prefix of the plugin, as the plugins are initialized again whenever the window opens
+    plugin: String,
+    data: CustomData,
+    action: String,
+    search_query: String,
+}
+
+/// runs the last action again, e.g. to roll the dice again
+const REPEAT_SHORTCUT: (Modifiers, Key) = (
+    Modifiers::CTRL.union(Modifiers::SHIFT),
+    Key::Character(SmolStr::new_inline("r")),
+);
+
+/// copies the names of all results, one per line
+const COPY_RESULTS_SHORTCUT: (Modifiers, Key) = (
+    Modifiers::CTRL.union(Modifiers::SHIFT),
+    Key::Character(SmolStr::new_inline("c")),
+);
+
+/// errors logged within this duration after an error popup was opened are added to that popup
+/// instead of opening a new one.
+const ERROR_POPUP_INTERVAL: Duration = Duration::from_secs(3);
+
+/// the most entries a single key repeat moves the selection by
+const MAX_NAVIGATION_STEP: usize = 5;
+
+/// how far popups are moved from the previous one when multiple are open
+const POPUP_OFFSET: f32 = 30.0;
+
+/// the modifiers action shortcuts can use. shift only counts together with one of the others, see
+/// [`keybind::is_shortcut_modifiers`].
 const ALLOWED_ACTION_MODIFIERS: Modifiers = Modifiers::COMMAND
     .union(Modifiers::ALT)
     .union(Modifiers::CTRL)
-    .union(Modifiers::LOGO);
+    .union(Modifiers::LOGO)
+    .union(Modifiers::SHIFT);
 
+#[derive(Clone)]
 pub struct Action {
     name: Cow<'static, str>,
     shortcut: (Modifiers, Key),
@@ -359,6 +585,9 @@ pub fn format_key(key: &Key, modifiers: Modifiers, s: &mut String) {
     if Modifiers::ALT.intersects(modifiers) {
         s.push_str("Alt + ");
     }
+    if Modifiers::SHIFT.intersects(modifiers) {
+        s.push_str("Shift + ");
+    }
     if Modifiers::LOGO.intersects(modifiers) {
         #[cfg(target_os = "windows")]
         s.push_str("Win + ");
@@ -382,9 +611,22 @@ pub fn format_key(key: &Key, modifiers: Modifiers, s: &mut String) {
     }
 }
 
+/// the key of key presses never has the modifiers applied, so a shortcut for shift + c is pressed
+/// as `c` with shift held down. characters are compared case-insensitively, so shortcuts defined
+/// as `C` work as well.
+fn shortcut_matches(shortcut: &(Modifiers, Key), modifiers: Modifiers, key: &Key) -> bool {
+    if shortcut.0 != modifiers {
+        return false;
+    }
+    match (&shortcut.1, key) {
+        (Key::Character(a), Key::Character(b)) => a.to_lowercase() == b.to_lowercase(),
+        (a, b) => a == b,
+    }
+}
+
 #[must_use]
-pub fn key_element(s: Cow<'_, str>) -> Element<'_, Message> {
-    container(text(s).size(16))
+pub fn key_element(s: Cow<'_, str>, size: f32) -> Element<'_, Message> {
+    container(text(s).size(size))
         .style(|theme| {
             container::dark(theme)
                 .background(color!(0x44403b))
@@ -414,6 +656,15 @@ fn button_style(selected: bool) -> impl Fn(&Theme, button::Status) -> button::St
     }
 }
 
+/// strips the plugin prefix from the query the same way the collector does
+fn query_without_prefix(query: &str, prefix: &str) -> Arc<str> {
+    let query = query.trim();
+    match query.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => query[prefix.len()..].trim().into(),
+        _ => query.into(),
+    }
+}
+
 fn set_window_height(window_id: window::Id, new_height: f32, resize: bool) -> Task<Message> {
     if !resize {
         return Task::none();
@@ -427,27 +678,64 @@ fn set_window_height(window_id: window::Id, new_height: f32, resize: bool) -> Ta
 }
 
 impl State {
+    /// the icon and display name of a plugin, shown in front of the subtitle of its results
+    fn plugin_label(&self, plugin: usize, size: f32) -> Element<'_, Message> {
+        let Some(plugin) = self.plugins.get(plugin) else {
+            return text("").size(size).into();
+        };
+        let name = text(plugin.any_display_name())
+            .size(size)
+            .style(text::default);
+        match plugin.any_display_icon() {
+            Some(icon) => row![svg(icon).width(size).height(size), name]
+                .spacing(4)
+                .align_y(Vertical::Center)
+                .into(),
+            None => name.into(),
+        }
+    }
+
     pub fn view(&self) -> MouseArea<'_, Message> {
-        let search_field = SearchInput::new(&self.search_query, self.text_input.clone());
-        let mut col = column![stack([
-            search_field.into(),
-            text(format!("{} / {}  ", self.selected + 1, self.results.len()))
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .align_x(Horizontal::Right)
+        let config = &*self.context.config;
+        let text_size = config.text_size(16.0);
+        let search_field =
+            SearchInput::new(self.visible_query(), self.text_input.clone(), text_size);
+        let chip = self.active_prefix.as_deref().map(|prefix| {
+            self.plugins
+                .iter()
+                .find(|v| v.any_prefix() == prefix)
+                .map_or(prefix, |v| v.any_display_name())
+        });
+        let search_field = match chip {
+            Some(name) => row![key_element(Cow::Borrowed(name), text_size), search_field]
+                .padding([0, 5])
                 .align_y(Vertical::Center)
-                .color(Color::from_rgb8(0x60, 0x60, 0x60))
-                .size(13)
-                .into()
-        ])];
+                .into(),
+            None => search_field.into(),
+        };
+        let mut search = vec![search_field];
+        if config.show_counter {
+            search.push(
+                text(format!("{} / {}  ", self.selected + 1, self.results.len()))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Horizontal::Right)
+                    .align_y(Vertical::Center)
+                    .color(Color::from_rgb8(0x60, 0x60, 0x60))
+                    .size(config.text_size(13.0))
+                    .into(),
+            );
+        }
+        let mut col = column![stack(search)];
 
+        let mut entries = column![];
         for entry_idx in 0..NUM_ENTRIES {
             let index = entry_idx + self.offset;
             if index >= self.results.len() {
-                if !self.context.config.auto_resize {
-                    col = col.push(
+                if !config.auto_resize {
+                    entries = entries.push(
                         vertical_space()
-                            .height(Length::Fixed(ENTRY_SIZE))
+                            .height(Length::Fixed(config.entry_size()))
                             .width(Length::Fill),
                     );
                     continue;
@@ -456,59 +744,82 @@ impl State {
             }
             let selected = index == self.selected;
             let entry = &self.results[entry_idx + self.offset];
+            if config.group_results
+                && (entry_idx == 0 || self.results[index - 1].plugin != entry.plugin)
+            {
+                entries = entries.push(
+                    container(
+                        text(
+                            self.plugins
+                                .get(entry.plugin)
+                                .map(|v| v.any_display_name())
+                                .unwrap_or_default(),
+                        )
+                        .size(config.text_size(13.0))
+                        .color(Color::from_rgb8(0x60, 0x60, 0x60)),
+                    )
+                    .height(config.header_size())
+                    .padding([0, 7])
+                    .align_y(Vertical::Center),
+                );
+            }
             let subtitle: Element<'_, Message> = if entry.subtitle.is_empty() {
-                text(
-                    self.plugins
-                        .get(entry.plugin)
-                        .map(|v| v.any_prefix())
-                        .unwrap_or_default(),
-                )
-                .size(16)
-                .into()
+                self.plugin_label(entry.plugin, text_size)
             } else {
                 row![
-                    text(
-                        self.plugins
-                            .get(entry.plugin)
-                            .map(|v| v.any_prefix())
-                            .unwrap_or_default()
-                    )
-                    .size(16)
-                    .style(text::default),
-                    text(" • ").size(16),
+                    self.plugin_label(entry.plugin, text_size),
+                    text(" • ").size(text_size),
                     text(&*entry.subtitle)
-                        .size(16)
+                        .size(text_size)
                         .wrapping(text::Wrapping::None),
                 ]
-                .height(20)
+                .height(config.text_size(20.0))
                 .width(Length::Fill)
                 .into()
             };
             let inner_col = column![
                 text(&*entry.name)
-                    .size(20)
-                    .height(25)
+                    .size(config.text_size(20.0))
+                    .height(config.text_size(25.0))
                     .wrapping(text::Wrapping::None),
                 subtitle
             ];
-            col = col.push(
-                button(inner_col)
-                    .width(Length::Fill)
-                    .height(Length::Fixed(ENTRY_SIZE))
-                    .style(button_style(selected))
-                    .on_press(Message::Click(entry_idx + self.offset)),
+            entries = entries.push(
+                mouse_area(
+                    button(inner_col)
+                        .width(Length::Fill)
+                        .height(Length::Fixed(config.entry_size()))
+                        .style(button_style(selected))
+                        .on_press(Message::Click(index)),
+                )
+                .on_right_press(Message::ShowActionsFor(index))
+                .on_enter(Message::Hover(entry_idx))
+                .on_exit(Message::Unhover(entry_idx)),
             );
         }
+        col = col.push(if config.group_results && !config.auto_resize {
+            // the window doesn't grow to fit the headers, so the last entries are cut off instead
+            entries.height(Length::Fill).clip(true)
+        } else {
+            entries
+        });
         if self.showing_actions {
             for (i, action) in self.get_actions().iter().enumerate() {
                 let description = if matches!(action.shortcut.1, Key::Unidentified) {
-                    row![text(&action.name).size(16).style(text::default)].spacing(10)
+                    row![
+                        text(action.name.to_string())
+                            .size(text_size)
+                            .style(text::default)
+                    ]
+                    .spacing(10)
                 } else {
                     let mut s = String::new();
                     format_key(&action.shortcut.1, action.shortcut.0, &mut s);
                     row![
-                        text(&action.name).size(16).style(text::default),
-                        key_element(s.into())
+                        text(action.name.to_string())
+                            .size(text_size)
+                            .style(text::default),
+                        key_element(s.into(), text_size)
                     ]
                     .spacing(10)
                 };
@@ -520,26 +831,22 @@ impl State {
                     )
                     .width(Length::Fill)
                     .style(button_style(self.selected_action == i))
-                    .height(ACTION_SIZE)
+                    .height(config.action_size())
                     .on_press(Message::None),
                 );
             }
         }
 
-        let (action_text, action_key, action_seperator) = match self
-            .results
-            .get(self.selected)
-            .and_then(|v| self.plugins.get(v.plugin))
-            .and_then(|v| v.any_actions().first())
-        {
+        let actions = self.entry_actions(self.selected);
+        let (action_text, action_key, action_seperator) = match actions.first() {
             None => (None, None, None),
             Some(action) => {
                 let mut s = String::new();
                 format_key(&action.shortcut.1, action.shortcut.0, &mut s);
                 (
-                    Some(text(&action.name).size(16)),
-                    Some(key_element(s.into())),
-                    Some(text("•").size(16)),
+                    Some(text(action.name.to_string()).size(text_size)),
+                    Some(key_element(s.into(), text_size)),
+                    Some(text("•").size(text_size)),
                 )
             }
         };
@@ -549,18 +856,19 @@ impl State {
                     .push_maybe(action_text)
                     .push_maybe(action_key)
                     .push_maybe(action_seperator)
-                    .push(text("Actions").size(16))
-                    .push(key_element("Alt".into()))
-                    .push(text("•").size(16))
-                    .push(
-                        text(utils::CRATE_NAME.to_string() + " v" + utils::CRATE_VERSION).size(16),
-                    )
+                    .push(text("Actions").size(text_size))
+                    .push(key_element("Alt".into(), text_size))
+                    .push_maybe(config.show_version.then(|| text("•").size(text_size)))
+                    .push_maybe(config.show_version.then(|| {
+                        text(utils::CRATE_NAME.to_string() + " v" + utils::CRATE_VERSION)
+                            .size(text_size)
+                    }))
                     .spacing(10)
                     .width(Length::Fill)
-                    .height(ACTION_BAR_SIZE)
+                    .height(config.action_bar_size())
                     .align_y(Vertical::Center),
             )
-            .height(ACTION_BAR_SIZE + 1.0)
+            .height(config.action_bar_size() + 1.0)
             .padding([0, 7])
             .style(|_| container::background(color!(0x79716b)).color(Color::WHITE)),
         );
@@ -576,28 +884,55 @@ impl State {
             }
         })
     }
-    fn get_actions(&self) -> &[Action] {
+    fn get_actions(&self) -> Cow<'_, [Action]> {
         if self.showing_actions {
-            self.results
-                .get(self.selected)
-                .and_then(|res| self.plugins.get(res.plugin))
-                .map(|v| v.any_actions())
-                .unwrap_or_default()
+            self.entry_actions(self.selected)
         } else {
-            &[]
+            Cow::Borrowed(&[])
+        }
+    }
+
+    /// the number of group headers above the visible entries
+    fn visible_headers(&self) -> usize {
+        if !self.context.config.group_results {
+            return 0;
         }
+        let end = (self.offset + NUM_ENTRIES).min(self.results.len());
+        (self.offset..end)
+            .filter(|&i| i == self.offset || self.results[i - 1].plugin != self.results[i].plugin)
+            .count()
+    }
+
+    /// the height of the window with auto resize enabled, without any actions
+    fn results_height(&self) -> f32 {
+        self.context.config.height_for(self.results.len())
+            + self.visible_headers() as f32 * self.context.config.header_size()
+    }
+
+    /// the actions of the result at `index`
+    fn entry_actions(&self, index: usize) -> Cow<'_, [Action]> {
+        self.results
+            .get(index)
+            .and_then(|res| Some(self.plugins.get(res.plugin)?.any_actions_for(&res.data())))
+            .unwrap_or_default()
     }
 
     fn update_matches(&mut self) {
-        if self.search_query.is_empty() {
+        // the query keeps its case, `MatcherInput::input` lowercases it for the plugins that don't
+        // care about it
+        let query = self.search_query.trim().to_string();
+        if query.is_empty() {
             self.results.clear();
-            return;
+            // an empty query only shows the favorites and the recent searches
+            if self.context.config.favorites.is_empty() && self.history_plugin().is_none() {
+                return;
+            }
         }
 
         if let Some(controller) = &mut self.collector_controller {
             controller.start(
                 self.plugins.as_slice().into(),
-                self.search_query.trim().to_lowercase(),
+                query,
                 self.context.clone(),
             );
         } else {
@@ -605,7 +940,7 @@ impl State {
         }
     }
 
-    fn run(&mut self, index: usize, selected_action: usize) -> iced::Task<Message> {
+    fn run(&mut self, index: usize, selected_action: usize, keep_open: bool) -> Task<Message> {
         if self.results.len() <= self.selected {
             return Task::none();
         }
@@ -613,41 +948,197 @@ impl State {
         if entry.plugin >= self.plugins.len() {
             return Task::none();
         }
-        let plugin = &self.plugins[entry.plugin];
-        let Some(action) = plugin.any_actions().get(selected_action) else {
+        let plugin = entry.plugin;
+        let data = entry.data();
+        let Some(action) = self.plugins[plugin]
+            .any_actions_for(&data)
+            .get(selected_action)
+            .cloned()
+        else {
             return Task::none();
         };
-        if action.closes {
-            let entry = self.results.remove(index);
+        if action.closes && !keep_open {
+            self.results.remove(index);
+        }
+        self.last_action = Some(LastAction {
+            plugin: self.plugins[plugin].any_prefix().to_string(),
+            data: data.clone(),
+            action: action.id.to_string(),
+            search_query: self.search_query.clone(),
+        });
+        let search_query = self.search_query.clone();
+        if action.closes && !keep_open && self.history_plugin().is_some() {
+            history_plugin::record(
+                &plugin_ctx_from_ctx!(self.context, HistoryPlugin::prefix()),
+                &search_query,
+            );
+        }
+        self.run_action(plugin, data, &action, &search_query, keep_open)
+    }
+
+    /// the history plugin if it's enabled. searches are only recorded if it is.
+    fn history_plugin(&self) -> Option<&Arc<dyn AnyPlugin>> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.any_prefix() == HistoryPlugin::prefix())
+    }
+
+    /// puts the next completion of the plugin whose prefix the query starts with into the search
+    /// bar. pressing tab again while it's still there moves on to the one after it, or completes
+    /// the query again if it was the only one. returns `None` if there's nothing to complete.
+    fn complete(&mut self, backwards: bool) -> Option<Task<Message>> {
+        if let Some(completions) = &mut self.completions
+            && completions.queries.len() > 1
+            && completions.queries.get(completions.index) == Some(&self.search_query)
+        {
+            let len = completions.queries.len();
+            completions.index = if backwards {
+                (completions.index + len - 1) % len
+            } else {
+                (completions.index + 1) % len
+            };
+            let query = completions.queries[completions.index].clone();
+            return Some(Task::done(Message::SetSearch(query)));
+        }
+        self.completions = None;
+        let plugin = self
+            .plugins
+            .iter()
+            .find(|v| self.search_query.starts_with(v.any_prefix()))?;
+        let prefix = plugin.any_prefix();
+        let queries = plugin
+            .any_completions(
+                &self.search_query[prefix.len()..],
+                plugin_ctx_from_ctx!(self.context, prefix),
+            )
+            .into_iter()
+            .map(|v| format!("{prefix}{v}"))
+            .collect::<Vec<_>>();
+        // a completion that is already in the search bar isn't worth moving to
+        let index = if backwards {
+            queries.iter().rposition(|v| *v != self.search_query)?
+        } else {
+            queries.iter().position(|v| *v != self.search_query)?
+        };
+        let query = queries[index].clone();
+        self.completions = Some(Completions { queries, index });
+        Some(Task::done(Message::SetSearch(query)))
+    }
+
+    /// runs [`Config::fallback`] with the query
+    fn run_fallback(&self, keep_open: bool) -> Task<Message> {
+        let query = self.search_query.trim();
+        if query.is_empty() {
+            return Task::none();
+        }
+        match self.context.config.fallback {
+            Fallback::None => return Task::none(),
+            Fallback::Shell => {
+                let mut cmd = Command::new("sh");
+                cmd.arg("-c").arg(query);
+                utils::run_cmd(cmd);
+            }
+            Fallback::WebSearch => {
+                let url = &self.context.config.web_search_url;
+                utils::open_link(url.replace("{}", &utils::percent_encode(query)));
+            }
+        }
+        if keep_open {
+            Task::none()
+        } else {
+            Task::done(Message::HideMainWindow)
+        }
+    }
+
+    /// runs the action of the last entry that was run again, if its plugin still offers it
+    fn repeat_last_action(&mut self) -> Task<Message> {
+        let Some(last) = &self.last_action else {
+            return Task::none();
+        };
+        let Some(plugin) = self
+            .plugins
+            .iter()
+            .position(|v| v.any_prefix() == last.plugin)
+        else {
+            log::error!(
+                "failed to repeat the last action: the plugin {} is no longer loaded",
+                last.plugin
+            );
+            return Task::none();
+        };
+        let Some(action) = self.plugins[plugin]
+            .any_actions_for(&last.data)
+            .iter()
+            .find(|v| v.id == last.action)
+            .cloned()
+        else {
+            log::error!(
+                "failed to repeat the last action: {} no longer offers the action {}",
+                last.plugin,
+                last.action
+            );
+            return Task::none();
+        };
+        let (data, search_query) = (last.data.clone(), last.search_query.clone());
+        self.run_action(plugin, data, &action, &search_query, false)
+    }
+
+    fn run_action(
+        &self,
+        plugin_idx: usize,
+        data: CustomData,
+        action: &Action,
+        search_query: &str,
+        keep_open: bool,
+    ) -> Task<Message> {
+        let plugin = &self.plugins[plugin_idx];
+        let query = query_without_prefix(search_query, plugin.any_prefix());
+        if action.closes && !keep_open {
             Task::batch([
                 plugin.any_handle_pre(
-                    entry.data.clone(),
+                    data.clone(),
                     &action.id,
-                    plugin_ctx_from_ctx!(self.context, plugin.any_prefix()),
+                    plugin_ctx_from_ctx!(self.context, plugin.any_prefix())
+                        .with_query(query.clone()),
                 ),
                 Task::done(Message::HideMainWindow),
                 Task::done(Message::HandleAction {
-                    plugin: entry.plugin,
-                    data: entry.data,
+                    plugin: plugin_idx,
+                    data,
                     action: action.id.to_string(),
+                    query,
                 }),
             ])
         } else {
             Task::batch([
                 plugin.any_handle_pre(
-                    entry.data.clone(),
+                    data.clone(),
                     &action.id,
-                    plugin_ctx_from_ctx!(self.context, plugin.any_prefix()),
+                    plugin_ctx_from_ctx!(self.context, plugin.any_prefix())
+                        .with_query(query.clone()),
                 ),
                 plugin.any_handle_post(
-                    entry.data.clone(),
+                    data,
                     &action.id,
-                    plugin_ctx_from_ctx!(self.context, plugin.any_prefix()),
+                    plugin_ctx_from_ctx!(self.context, plugin.any_prefix()).with_query(query),
                 ),
             ])
         }
     }
 
+    /// how far the selection moves per key repeat. it gets faster the longer the key is held if
+    /// `accelerate_navigation` is enabled.
+    fn held_navigation_step(&self) -> usize {
+        if !self.context.config.accelerate_navigation {
+            return 1;
+        }
+        let held_for = self
+            .navigation_held_since
+            .map_or(Duration::ZERO, |v| v.elapsed());
+        usize::try_from(held_for.as_millis() / 500)
+            .map_or(MAX_NAVIGATION_STEP, |v| (v + 1).min(MAX_NAVIGATION_STEP))
+    }
+
     fn handle_go_up(&mut self, amount: usize) {
         if self.showing_actions {
             self.selected_action = self.selected_action.saturating_sub(amount);
@@ -657,14 +1148,69 @@ impl State {
     }
 
     fn handle_go_down(&mut self, amount: usize) {
-        let actions = self.get_actions();
-        if self.showing_actions && !actions.is_empty() {
-            self.selected_action = (self.selected_action + amount).min(actions.len() - 1);
+        let actions_len = self.get_actions().len();
+        if self.showing_actions && actions_len > 0 {
+            self.selected_action = (self.selected_action + amount).min(actions_len - 1);
         } else if !self.results.is_empty() {
             self.selected = (self.selected + amount).min(self.results.len() - 1);
         }
     }
 
+    fn show_actions(&mut self, window_id: window::Id) -> Task<Message> {
+        if self.results.is_empty() {
+            return Task::none();
+        }
+        let actions_len = self.entry_actions(self.selected).len();
+        if actions_len == 0 {
+            return Task::none();
+        }
+        self.showing_actions = true;
+        self.selected_action = 0;
+        let new_height = if self.context.config.auto_resize {
+            self.results_height()
+        } else {
+            self.context.config.noresize_base_size()
+        };
+        let new_height = new_height + actions_len as f32 * self.context.config.action_size();
+        set_window_height(window_id, new_height, true)
+    }
+
+    fn set_search_query(&mut self, mut query: String) {
+        let max_len = self.context.config.max_query_length();
+        if utils::truncate_at_char_boundary(&mut query, max_len) {
+            log::info!("the query was cut off after {max_len} bytes");
+        }
+        self.search_query = query;
+    }
+
+    /// the part of the query that is shown in the search bar, after the prefix chip
+    fn visible_query(&self) -> &str {
+        self.active_prefix
+            .as_deref()
+            .and_then(|prefix| self.search_query.strip_prefix(prefix))
+            .and_then(|query| query.strip_prefix(' '))
+            .unwrap_or(&self.search_query)
+    }
+
+    /// shows the prefix the query starts with as a chip if it's followed by a space, or drops
+    /// the chip if the query was replaced with one that doesn't start with it. returns if a chip
+    /// was added.
+    fn update_active_prefix(&mut self) -> bool {
+        if self.active_prefix.is_some() {
+            if self.visible_query().len() == self.search_query.len() {
+                self.active_prefix = None;
+            }
+            return false;
+        }
+        let prefix = self.plugins.iter().map(|v| v.any_prefix()).find(|prefix| {
+            self.search_query
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with(' '))
+        });
+        self.active_prefix = prefix.map(str::to_string);
+        self.active_prefix.is_some()
+    }
+
     fn hide_actions(&mut self) {
         self.showing_actions = false;
         self.selected_action = 0;
@@ -674,9 +1220,11 @@ impl State {
         let Some(window_id) = self.window else {
             unreachable!("the window update should always have a window")
         };
+        let previous_offset = self.offset;
         match message {
             Message::SetSearch(q) => {
-                self.search_query = q;
+                self.set_search_query(q);
+                self.update_active_prefix();
                 self.update_matches();
                 self.selected = 0;
                 self.hide_actions();
@@ -684,49 +1232,105 @@ impl State {
                 if self.search_query.is_empty() {
                     return Task::batch([
                         task,
-                        set_window_height(window_id, BASE_SIZE, self.context.config.auto_resize),
+                        set_window_height(
+                            window_id,
+                            self.context.config.base_size(),
+                            self.context.config.auto_resize,
+                        ),
                     ]);
                 }
                 return task;
             }
             Message::UpdateSearch(q) => {
-                self.search_query = q;
+                let q = match &self.active_prefix {
+                    Some(prefix) => format!("{prefix} {q}"),
+                    None => q,
+                };
+                self.set_search_query(q);
+                let added_chip = self.update_active_prefix();
                 self.update_matches();
                 self.selected = 0;
                 self.hide_actions();
                 if self.search_query.is_empty() {
                     return set_window_height(
                         window_id,
-                        BASE_SIZE,
+                        self.context.config.base_size(),
                         self.context.config.auto_resize,
                     );
                 }
+                // the prefix moved out of the search bar, so the cursor would be past the end
+                if added_chip {
+                    return text_input::move_cursor_to_end(self.text_input.clone());
+                }
+            }
+            Message::RemovePrefix => {
+                if self.active_prefix.take().is_some() {
+                    return Task::done(Message::UpdateSearch(String::new()));
+                }
             }
             Message::AddPlugin(plugin) => {
+                // a plugin that was initialized again replaces the old instance in place, so the
+                // indices of the other plugins stay valid
+                let prefix = plugin.0.any_prefix();
+                if let Some(old) = self.plugins.iter_mut().find(|v| v.any_prefix() == prefix) {
+                    *old = plugin.0;
+                    self.update_matches();
+                    return Task::none();
+                }
+                // the plugins are initialized when the window is shown, so a new one was just
+                // shown
+                let task = plugin
+                    .0
+                    .any_on_show(plugin_ctx_from_ctx!(self.context, plugin.0.any_prefix()));
                 self.plugins.push(plugin.0);
                 self.update_matches();
+                return task;
             }
             Message::KeyPressed(key, modifiers) => {
-                if let Some(action) = self
-                    .results
-                    .get(self.selected)
-                    .and_then(|v| self.plugins.get(v.plugin))
-                    .and_then(|plugin| {
-                        plugin
-                            .any_actions()
-                            .iter()
-                            .position(|v| v.shortcut.0 == modifiers && v.shortcut.1 == key)
-                    })
+                // completions take precedence over the actions that are run with tab
+                if key == Key::Named(Named::Tab)
+                    && (modifiers.is_empty() || modifiers == Modifiers::SHIFT)
+                    && let Some(task) = self.complete(modifiers.shift())
                 {
-                    return self.run(self.selected, action);
+                    return task;
+                }
+                let action = self
+                    .entry_actions(self.selected)
+                    .iter()
+                    .position(|v| shortcut_matches(&v.shortcut, modifiers, &key));
+                if let Some(action) = action {
+                    return self.run(self.selected, action, false);
+                }
+                if shortcut_matches(&REPEAT_SHORTCUT, modifiers, &key) {
+                    return self.repeat_last_action();
+                }
+                // the window stays open, so the results can be copied while searching
+                if shortcut_matches(&COPY_RESULTS_SHORTCUT, modifiers, &key) {
+                    let names = self
+                        .results
+                        .iter()
+                        .map(|entry| entry.name.to_str())
+                        .collect::<Vec<_>>();
+                    return iced::clipboard::write(names.join("\n"));
                 }
             }
             Message::ResultsUpdated => self.update_matches(),
-            Message::GoUp => self.handle_go_up(1),
+            Message::GoUp => {
+                self.navigation_held_since = Some(Instant::now());
+                self.handle_go_up(1);
+            }
             Message::Go10Up => self.handle_go_up(10),
-            Message::GoDown => self.handle_go_down(1),
+            Message::GoDown => {
+                self.navigation_held_since = Some(Instant::now());
+                self.handle_go_down(1);
+            }
             Message::Go10Down => self.handle_go_down(10),
-            Message::Submit => {
+            Message::HeldGoUp => self.handle_go_up(self.held_navigation_step()),
+            Message::HeldGoDown => self.handle_go_down(self.held_navigation_step()),
+            Message::Submit(keep_open) => {
+                if self.results.is_empty() && self.active_prefix.is_none() {
+                    return self.run_fallback(keep_open);
+                }
                 return self.run(
                     self.selected,
                     if self.showing_actions {
@@ -734,6 +1338,7 @@ impl State {
                     } else {
                         0
                     },
+                    keep_open,
                 );
             }
             Message::Click(index) => {
@@ -747,23 +1352,42 @@ impl State {
                 if self.selected >= self.offset + NUM_ENTRIES {
                     self.offset = self.selected + 1 - NUM_ENTRIES;
                 }
-                return self.run(index, 0);
+                return self.run(index, 0, false);
             }
             Message::HideMainWindow => {
+                let hide_tasks = Task::batch(self.plugins.iter().map(|plugin| {
+                    plugin.any_on_hide(plugin_ctx_from_ctx!(self.context, plugin.any_prefix()))
+                }));
                 self.search_query.clear();
+                if !self.context.config.sticky_prefix {
+                    self.active_prefix = None;
+                }
+                // a sticky prefix stays until it's removed
+                if let Some(prefix) = &self.active_prefix {
+                    self.search_query = format!("{prefix} ");
+                }
                 self.results.clear();
+                self.hovered_entry = None;
                 self.hide_actions();
-                self.initializing_plugins
-                    .iter()
-                    .for_each(AbortHandle::abort);
-                self.initializing_plugins.clear();
+                self.cancel_plugin_init();
                 if let Some(v) = self.collector_controller.as_mut() {
                     v.stop();
                 }
                 self.window = None;
-                return iced::window::close(window_id);
+                if let Some(height) = self.resized_height.take()
+                    && (height - self.context.config.noresize_base_size()).abs() >= 1.0
+                {
+                    let mut config = (*self.context.config).clone();
+                    config.fixed_height = Some(height);
+                    return Task::batch([
+                        hide_tasks,
+                        iced::window::close(window_id),
+                        Task::done(Message::UpdateConfig(config.into(), true)),
+                    ]);
+                }
+                return Task::batch([hide_tasks, iced::window::close(window_id)]);
             }
-            Message::ChangeTheme(theme) => self.theme = theme,
+            Message::ChangeTheme(theme) => self.context.theme = theme,
             Message::InputPress => {
                 let Some(window) = self.window else {
                     return text_input::focus(self.text_input.clone());
@@ -776,36 +1400,23 @@ impl State {
             Message::CollectorMessage(CollectorMessage::Finished(results)) => {
                 self.hide_actions();
                 self.results = results;
-                let new_height =
-                    self.results.len().min(NUM_ENTRIES) as f32 * ENTRY_SIZE + BASE_SIZE;
+                let new_height = self.results_height();
                 return set_window_height(window_id, new_height, self.context.config.auto_resize);
             }
-            Message::ShowActions => {
-                if self.results.is_empty() {
+            Message::ShowActions => return self.show_actions(window_id),
+            Message::ShowActionsFor(index) => {
+                if index >= self.results.len() {
                     return Task::none();
                 }
-                let Some(plugin) = self.plugins.get(self.results[self.selected].plugin) else {
-                    return Task::none();
-                };
-                let actions = plugin.any_actions();
-                if !self.results.is_empty() {
-                    self.showing_actions = true;
-                    self.selected_action = 0;
-                    let new_height = if self.context.config.auto_resize {
-                        self.results.len().min(NUM_ENTRIES) as f32 * ENTRY_SIZE + BASE_SIZE
-                    } else {
-                        NORESIZE_BASESIZE
-                    };
-                    let new_height = new_height + actions.len() as f32 * ACTION_SIZE;
-                    return set_window_height(window_id, new_height, true);
-                }
+                self.selected = index;
+                return self.show_actions(window_id);
             }
             Message::HideActions => {
                 self.hide_actions();
                 let new_height = if self.context.config.auto_resize {
-                    self.results.len().min(NUM_ENTRIES) as f32 * ENTRY_SIZE + BASE_SIZE
+                    self.results_height()
                 } else {
-                    NORESIZE_BASESIZE
+                    self.context.config.noresize_base_size()
                 };
                 return set_window_height(window_id, new_height, true);
             }
@@ -814,18 +1425,49 @@ impl State {
                 BlurAction::None => {}
             },
             Message::Blurred(_) => {}
+            Message::Hover(entry_idx) => self.hovered_entry = Some(entry_idx),
+            Message::Unhover(entry_idx) => {
+                if self.hovered_entry == Some(entry_idx) {
+                    self.hovered_entry = None;
+                }
+            }
+            Message::CursorMoved(id) => {
+                if let Some(entry_idx) = self.hovered_entry
+                    && id == window_id
+                    && !self.showing_actions
+                    && entry_idx + self.offset < self.results.len()
+                {
+                    self.selected = entry_idx + self.offset;
+                }
+            }
+            // the action list changes the height as well, so only heights without it are kept
+            Message::Resized(id, size)
+                if id == window_id
+                    && !self.context.config.auto_resize
+                    && !self.showing_actions =>
+            {
+                self.resized_height = Some(size.height);
+            }
+            Message::Resized(..) => {}
 
             // daemon messages
             Message::Show
             | Message::OpenSpecial(_)
+            | Message::ShowError(_)
+            | Message::CopyToClipboard(_)
             | Message::Hide(_)
+            | Message::PopupTimeout(_)
             | Message::HandleAction { .. }
             | Message::None
             | Message::Exit
+            | Message::OpenSettings
             | Message::IndexerMessage(_)
             | Message::GetContext(_)
             | Message::UpdateConfig(..)
+            | Message::SetPluginConfig { .. }
             | Message::HotkeyPressed(_)
+            | Message::ApplicationsChanged
+            | Message::ClipboardChanged(_)
             | Message::SpecialWindow(..)
             | Message::CollectorMessage(CollectorMessage::Ready(_)) => unreachable!(),
         }
@@ -835,6 +1477,14 @@ impl State {
         if self.selected >= self.offset + NUM_ENTRIES {
             self.offset = self.selected + 1 - NUM_ENTRIES;
         }
+        // scrolling can change how many group headers are visible
+        if self.offset != previous_offset && self.context.config.group_results {
+            return set_window_height(
+                window_id,
+                self.results_height(),
+                self.context.config.auto_resize,
+            );
+        }
         Task::none()
     }
 
@@ -897,9 +1547,18 @@ impl State {
             if ext != "lua" {
                 continue;
             }
+            let granted = self
+                .context
+                .config
+                .lua_capabilities
+                .get(stem)
+                .map_or(&[][..], Vec::as_slice);
             let stem = Arc::<str>::from(stem);
-            match lua::load_lua_plugin(&self.lua, path, stem.clone()) {
-                Ok(v) => self.add_plugin_instance(v, stem),
+            match lua::load_lua_plugin_file(&self.lua, &path, stem.clone(), granted) {
+                Ok(v) => {
+                    self.lua_plugins.push(v.clone());
+                    self.add_plugin_instance(v, stem);
+                }
                 Err(e) => {
                     log::error!("Failed to load plugin {stem:?}: {e}");
                 }
@@ -907,51 +1566,118 @@ impl State {
         }
     }
 
+    pub fn add_native_plugins(&mut self) {
+        log::debug!("Loading native plugins...");
+        let Ok(dirent) = std::fs::read_dir(&*native_plugin::NATIVE_PLUGIN_DIR) else {
+            return;
+        };
+        for ent in dirent.filter_map(Result::ok) {
+            let path = ent.path();
+            if path
+                .extension()
+                .is_none_or(|ext| ext != std::env::consts::DLL_EXTENSION)
+            {
+                continue;
+            }
+            match native_plugin::load_native_plugin(&path) {
+                Ok(v) => {
+                    let prefix = Arc::<str>::from(v.prefix());
+                    self.add_plugin_instance(v, prefix);
+                }
+                Err(e) => {
+                    log::error!("Failed to load native plugin {}: {e}", path.display());
+                }
+            }
+        }
+    }
+
+    /// stops the initialization of the plugins that are still initializing
+    fn cancel_plugin_init(&mut self) {
+        self.init_cancelled.store(true, Ordering::Relaxed);
+        self.init_cancelled = Arc::default();
+        self.initializing_plugins
+            .iter()
+            .for_each(AbortHandle::abort);
+        self.initializing_plugins.clear();
+    }
+
     pub fn init_plugins(&mut self) {
         if let Some(controller) = &mut self.collector_controller {
             controller.stop();
         }
+        self.cancel_plugin_init();
         self.results.clear();
         self.plugins.clear();
-        for plugin_builder in self.plugin_builder.iter_mut().map(|(_, v)| v) {
-            let mut plugin = plugin_builder();
-            let prefix = plugin.any_prefix();
-            if prefix != "control"
-                && !self
-                    .context
-                    .config
-                    .enabled_plugins
-                    .iter()
-                    .any(|v| v == prefix)
-            {
-                continue;
-            }
-            let context = self.context.clone();
-            let sender = context.message_sender.clone();
-            self.initializing_plugins.push(
-                tokio::spawn(async move {
-                    plugin
-                        .any_init(PluginContext::from_context(
-                            &context,
-                            context
-                                .config
-                                .plugin_settings
-                                .as_ref_async()
-                                .await
-                                .get_root(plugin.any_prefix()),
-                        ))
-                        .await;
-                    sender
-                        .send(Message::AddPlugin(SharedAnyPlugin(plugin.into())))
-                        .await;
-                })
-                .abort_handle(),
-            );
+        for i in 0..self.plugin_builder.len() {
+            self.init_plugin(i);
+        }
+    }
+
+    /// builds and initializes the plugin with the prefix again, replacing the current instance
+    /// once it's done. does nothing if the plugin is disabled.
+    pub fn reinit_plugin(&mut self, prefix: &str) {
+        if let Some(i) = self.plugin_builder.iter().position(|(id, _)| id.to_str() == prefix) {
+            self.init_plugin(i);
         }
     }
 
+    /// builds the nth plugin and initializes it in the background if it's enabled
+    fn init_plugin(&mut self, i: usize) {
+        let mut plugin = (self.plugin_builder[i].1)();
+        let prefix = plugin.any_prefix();
+        if !ALWAYS_ENABLED_PLUGINS.contains(&prefix)
+            && !self
+                .context
+                .config
+                .enabled_plugins
+                .iter()
+                .any(|v| v == prefix)
+        {
+            return;
+        }
+        let context = self.context.clone();
+        let sender = context.message_sender.clone();
+        let cancelled = self.init_cancelled.clone();
+        self.initializing_plugins.push(
+            tokio::spawn(async move {
+                let settings = context.config.plugin_settings.as_ref_async().await;
+                let init = plugin.any_init(
+                    PluginContext::from_context(&context, settings.get_root(plugin.any_prefix()))
+                        .with_cancellation(cancelled),
+                );
+                // a panicking plugin would otherwise just be missing without any explanation
+                if let Err(e) = AssertUnwindSafe(init).catch_unwind().await {
+                    let reason = e
+                        .downcast_ref::<&str>()
+                        .copied()
+                        .or_else(|| e.downcast_ref::<String>().map(String::as_str))
+                        .unwrap_or("it panicked");
+                    log::error!(
+                        "Failed to initialize plugin `{}`: {reason}",
+                        plugin.any_prefix()
+                    );
+                    return;
+                }
+                sender
+                    .send(Message::AddPlugin(SharedAnyPlugin(plugin.into())))
+                    .await;
+            })
+            .abort_handle(),
+        );
+    }
+
     pub fn save_config(&self) {
-        let s = match toml::to_string_pretty(&*self.context.config) {
+        let s = if utils::config_is_json() {
+            serde_json::to_string_pretty(&*self.context.config).map_err(|e| e.to_string())
+        } else {
+            // written on top of the current file to keep the user's comments and formatting
+            let previous = std::fs::read_to_string(&*CONFIG_FILE).unwrap_or_default();
+            self.context
+                .config
+                .to_toml_keeping_format(&previous)
+                .map_err(|e| e.to_string())
+        };
+        let s = match s {
             Ok(v) => v,
             Err(e) => {
                 log::error!("Failed to save the config: {e}");
@@ -985,13 +1711,7 @@ pub fn change_theme(new_theme: Theme) -> Task<Message> {
     Task::done(Message::ChangeTheme(new_theme))
 }
 
-const SEARCH_SIZE: f32 = 31.0;
-const ENTRY_SIZE: f32 = 56.0;
-const ACTION_SIZE: f32 = 31.0;
-const ACTION_BAR_SIZE: f32 = 31.0;
-const BASE_SIZE: f32 = SEARCH_SIZE + ACTION_BAR_SIZE;
 const NUM_ENTRIES: usize = 10;
-const NORESIZE_BASESIZE: f32 = BASE_SIZE + NUM_ENTRIES as f32 * ENTRY_SIZE;
 
 fn daemon_view(state: &State, id: window::Id) -> Element<'_, Message> {
     if let Some(main_window_id) = state.window
@@ -1017,21 +1737,26 @@ fn daemon_update(state: &mut State, message: Message) -> Task<Message> {
         }
         Message::Show => {
             let mut settings = Settings {
-                resizable: false,
+                resizable: !state.context.config.auto_resize,
                 decorations: false,
                 level: Level::AlwaysOnTop,
                 position: Position::Centered,
                 ..Default::default()
             };
-            settings.size.height = NORESIZE_BASESIZE;
+            settings.size.height = state.context.config.noresize_base_size();
             if state.context.config.auto_resize {
+                // this can't capture the config, so the default sizes are used, which is close
+                // enough to roughly center the window.
                 settings.position = Position::SpecificWith(|winsize, resolution| {
+                    let height = resolution.height
+                        - config::DEFAULT_BASE_SIZE
+                        - 12.0 * config::DEFAULT_ROW_HEIGHT;
                     Point::new(
                         (resolution.width - winsize.width).max(0.0) / 2.0,
-                        (resolution.height - BASE_SIZE - 12.0 * ENTRY_SIZE).max(0.0) / 2.0,
+                        height.max(0.0) / 2.0,
                     )
                 });
-                settings.size.height = BASE_SIZE;
+                settings.size.height = state.context.config.base_size();
             }
             let (id, open_window_task) = window::open(settings);
             let open_window_task = open_window_task.map(|_| Message::None);
@@ -1051,20 +1776,39 @@ fn daemon_update(state: &mut State, message: Message) -> Task<Message> {
                 return Task::done(Message::HideMainWindow);
             }
             state.special_windows.remove(&window_id);
+            state.popup_deadlines.remove(&window_id);
             window::close(window_id)
         }
+        Message::PopupTimeout(id) => {
+            let Some(&deadline) = state.popup_deadlines.get(&id) else {
+                return Task::none();
+            };
+            let now = Instant::now();
+            if now < deadline {
+                return Task::perform(tokio::time::sleep(deadline - now), move |()| {
+                    Message::PopupTimeout(id)
+                });
+            }
+            state.popup_deadlines.remove(&id);
+            Task::done(Message::Hide(id))
+        }
         Message::HandleAction {
             plugin,
             data,
             action,
+            query,
         } => state.plugins.get(plugin).map_or_else(Task::none, |plugin| {
             plugin.any_handle_post(
                 data,
                 &action,
-                plugin_ctx_from_ctx!(state.context, plugin.any_prefix()),
+                plugin_ctx_from_ctx!(state.context, plugin.any_prefix()).with_query(query),
             )
         }),
         Message::Exit => iced::exit(),
+        Message::OpenSettings => Task::done(Message::OpenSpecial(SpecialWindowState::settings(
+            Clone::clone(&*state.context.config),
+        ))),
+        Message::CopyToClipboard(text) => iced::clipboard::write(text),
         Message::None => Task::none(),
         Message::IndexerMessage(FileIndexResponse::IndexFinished) if state.window.is_none() => {
             Task::none()
@@ -1099,10 +1843,33 @@ fn daemon_update(state: &mut State, message: Message) -> Task<Message> {
                     log::error!("config for plugin `{}` is incorrect", plugin.to_str());
                 }
             }
+            if cfg.autostart != state.context.config.autostart {
+                utils::set_autostart(cfg.autostart);
+            }
+            logging::apply_config(&cfg);
+            let http_task = if cfg.http == state.context.config.http {
+                Task::none()
+            } else {
+                let http_cache = state.context.http_cache.clone();
+                let http = cfg.http.clone();
+                Task::perform(
+                    async move { http_cache.write().await.apply_config(&http) },
+                    |()| Message::None,
+                )
+            };
             state.context.config = cfg;
             if save {
                 state.save_config();
             }
+            let hooks = Task::batch(
+                state
+                    .lua_plugins
+                    .iter()
+                    .map(|plugin| {
+                        plugin.config_changed(plugin_ctx_from_ctx!(state.context, plugin.prefix()))
+                    })
+                    .chain([http_task]),
+            );
             if state.window.is_some() {
                 state.update_matches();
             }
@@ -1112,26 +1879,63 @@ fn daemon_update(state: &mut State, message: Message) -> Task<Message> {
                 // will already have produced an error message
                 _ = sender.send(FileIndexMessage::SetConfig(state.context.config.clone()));
             }
-            if let Err(e) = state.manager.unregister(state.hotkey) {
-                log::error!("failed to unregister hotkey: {e}");
+            // the old hotkey is only unregistered once the new one works, so the window can
+            // still be opened if it doesn't
+            if state.hotkey != Some(hotkey)
+                && register_hotkey(&state.manager, hotkey, &state.context.config.keybind, "keybind")
+            {
+                if let Some(old) = state.hotkey
+                    && let Err(e) = state.manager.unregister(old)
+                {
+                    log::error!("failed to unregister hotkey: {e}");
+                }
+                state.hotkey = Some(hotkey);
             }
-            if let Err(e) = state.manager.register(hotkey) {
-                log::error!("failed to register hotkey: {e}");
+            let paste_hotkey = paste_hotkey(&state.context.config);
+            if state.paste_hotkey != paste_hotkey {
+                if let Some(old) = state.paste_hotkey.take()
+                    && let Err(e) = state.manager.unregister(old)
+                {
+                    log::error!("failed to unregister hotkey: {e}");
+                }
+                state.paste_hotkey = paste_hotkey
+                    .filter(|&v| register_paste_hotkey(&state.manager, v, &state.context.config));
             }
-            state.hotkey = hotkey;
             let Some(id) = state.window else {
-                return Task::none();
+                return hooks;
             };
-            if state.context.config.auto_resize {
-                let mut new_height =
-                    state.results.len().min(NUM_ENTRIES) as f32 * ENTRY_SIZE + BASE_SIZE;
+            let resize = if state.context.config.auto_resize {
+                let mut new_height = state.results_height();
                 if state.showing_actions {
-                    new_height += state.get_actions().len() as f32 * ACTION_SIZE;
+                    new_height +=
+                        state.get_actions().len() as f32 * state.context.config.action_size();
                 }
                 set_window_height(id, new_height, true)
             } else {
-                set_window_height(id, NORESIZE_BASESIZE, true)
-            }
+                set_window_height(id, state.context.config.noresize_base_size(), true)
+            };
+            Task::batch([hooks, resize])
+        }
+        Message::SetPluginConfig { plugin, key, value } => {
+            let Some(mut config) = state
+                .context
+                .config
+                .plugin_settings
+                .as_ref()
+                .get_root(&plugin)
+                .map(|v| (**v).clone())
+            else {
+                log::error!("failed to set `{key}`: plugin `{plugin}` has no config");
+                return Task::none();
+            };
+            let PluginSettingsValue::Map(ref mut values) = config else {
+                log::error!("failed to set `{key}`: the config of plugin `{plugin}` is malformed");
+                return Task::none();
+            };
+            values.insert(key, value);
+            state.context.config.plugin_settings.set(&plugin, config);
+            state.save_config();
+            Task::none()
         }
         Message::GetContext(sender) => {
             // it is fine to ignore the error, because it's either full or disconnected.
@@ -1145,27 +1949,123 @@ fn daemon_update(state: &mut State, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::OpenSpecial(window_state) => {
-            let (id, task) = if let Some(size) = window_state.size() {
+            let is_popup = window_state.is_popup();
+            let is_error = matches!(window_state, SpecialWindowState::ErrorPopup(_));
+            let (id, task) = if let Some(size) = window_state.size(&state.context.config) {
                 window::open(Settings {
                     size,
-                    resizable: false,
+                    // the settings scroll, so they can be made larger or smaller
+                    resizable: matches!(window_state, SpecialWindowState::Settings(_)),
                     level: Level::AlwaysOnTop,
+                    position: Position::Centered,
                     ..Default::default()
                 })
             } else {
                 window::open(Settings::default())
             };
             log::trace!("Opened special window {window_state:?} {id:?}");
+            let open_popups = state
+                .special_windows
+                .values()
+                .filter(|v| v.is_popup())
+                .count();
             state.special_windows.insert(id, window_state);
-            task.map(|_| Message::None)
+            if !is_popup {
+                return task.map(|_| Message::None);
+            }
+            // move the popup a bit so multiple popups don't cover each other
+            let offset = (open_popups % 10) as f32 * POPUP_OFFSET;
+            let open_task = if open_popups % 10 == 0 {
+                task.map(|_| Message::None)
+            } else {
+                task.then(move |id| {
+                    window::position(id).then(move |position| match position {
+                        Some(position) => {
+                            window::move_to(id, position + Vector::new(offset, offset))
+                        }
+                        None => Task::none(),
+                    })
+                })
+            };
+            match state.context.config.popup_timeout(is_error) {
+                Some(timeout) => {
+                    state.popup_deadlines.insert(id, Instant::now() + timeout);
+                    Task::batch([
+                        open_task,
+                        Task::perform(tokio::time::sleep(timeout), move |()| {
+                            Message::PopupTimeout(id)
+                        }),
+                    ])
+                }
+                None => open_task,
+            }
+        }
+        Message::ShowError(message) => {
+            if state.special_windows.values().any(|window| {
+                matches!(window, SpecialWindowState::ErrorPopup(popup) if popup.contains(&message))
+            }) {
+                return Task::none();
+            }
+            if state
+                .last_error_popup
+                .is_some_and(|v| v.elapsed() < ERROR_POPUP_INTERVAL)
+                && let Some((id, SpecialWindowState::ErrorPopup(popup))) = state
+                    .special_windows
+                    .iter_mut()
+                    .rev()
+                    .find(|(_, window)| matches!(window, SpecialWindowState::ErrorPopup(_)))
+            {
+                popup.push(message);
+                // the new error should be visible for the whole timeout as well
+                if let Some(deadline) = state.popup_deadlines.get_mut(id)
+                    && let Some(timeout) = state.context.config.popup_timeout(true)
+                {
+                    *deadline = Instant::now() + timeout;
+                }
+                return Task::none();
+            }
+            state.last_error_popup = Some(Instant::now());
+            Task::done(Message::OpenSpecial(SpecialWindowState::new_error_popup(
+                message,
+            )))
         }
         Message::HotkeyPressed(ev) => {
-            if ev.state() == HotKeyState::Pressed && ev.id == state.hotkey.id {
+            if ev.state() != HotKeyState::Pressed {
+                Task::none()
+            } else if state.hotkey.is_some_and(|v| v.id == ev.id) {
                 Task::done(Message::Show)
+            } else if state.paste_hotkey.is_some_and(|v| v.id == ev.id) {
+                // the clipboard is read by the window, so it has to be opened first. anything that
+                // isn't text opens an empty search.
+                Task::done(Message::Show).chain(iced::clipboard::read().map(|text| {
+                    let text = text.unwrap_or_default();
+                    Message::SetSearch(text.lines().next().unwrap_or_default().trim().to_string())
+                }))
             } else {
                 Task::none()
             }
         }
+        // the run plugin is initialized whenever the window is shown, so only an open window
+        // needs to pick up the changes
+        Message::ApplicationsChanged => {
+            if state.window.is_some() {
+                state.reinit_plugin(RunPlugin::prefix());
+            }
+            Task::none()
+        }
+        Message::ClipboardChanged(text) => {
+            let history = state.context.clipboard_history.clone();
+            Task::perform(
+                async move {
+                    let mut history = history.write().await;
+                    // copying the same text again moves it to the top
+                    history.retain(|v| *v != text);
+                    history.push_front(text);
+                    history.truncate(CLIPBOARD_HISTORY_LEN);
+                },
+                |()| Message::None,
+            )
+        }
         _ if state.window.is_none() => Task::none(),
         _ => state.update(message),
     }
@@ -1188,8 +2088,29 @@ fn load_config() -> Option<Config> {
             return None;
         }
     };
-    match toml::from_str(&content) {
-        Ok(v) => v,
+    // serde ignores keys it doesn't know, which hides typos, so they're collected and reported
+    let mut unknown_keys = Vec::new();
+    let on_unknown = |path: serde_ignored::Path<'_>| unknown_keys.push(path.to_string());
+    let config = if utils::config_is_json() {
+        let mut deserializer = serde_json::Deserializer::from_str(&content);
+        serde_ignored::deserialize(&mut deserializer, on_unknown)
+            .and_then(|v| deserializer.end().map(|()| v))
+            .map_err(|e| e.to_string())
+    } else {
+        serde_ignored::deserialize(toml::Deserializer::new(&content), on_unknown)
+            .map_err(|e| e.to_string())
+    };
+    match config {
+        Ok(v) => {
+            if !unknown_keys.is_empty() {
+                log::warn!(
+                    "the config contains unknown keys, which are ignored: {}\nPlease edit the config at {}",
+                    unknown_keys.join(", "),
+                    CONFIG_FILE.display()
+                );
+            }
+            Some(v)
+        }
         Err(e) => {
             log::error!("failed to load config: {e}");
             None
@@ -1197,13 +2118,129 @@ fn load_config() -> Option<Config> {
     }
 }
 
+/// the hotkey of [`Config::paste_keybind`], if it is set and valid
+fn paste_hotkey(config: &Config) -> Option<HotKey> {
+    let keybind = config.paste_keybind.as_deref()?;
+    let hotkey = keybind::key_and_modifiers_from_str(keybind).and_then(keybind::iced_to_hotkey);
+    if hotkey.is_none() {
+        log::error!("failed to load the paste hotkey: {keybind:?} is not a valid keybind");
+    }
+    hotkey
+}
+
+fn register_paste_hotkey(manager: &GlobalHotKeyManager, hotkey: HotKey, config: &Config) -> bool {
+    let keybind = config.paste_keybind.as_deref().unwrap_or_default();
+    register_hotkey(manager, hotkey, keybind, "paste_keybind")
+}
+
+/// registers the hotkey, showing an error if that failed. returns if it was registered.
+fn register_hotkey(
+    manager: &GlobalHotKeyManager,
+    hotkey: HotKey,
+    keybind: &str,
+    option: &str,
+) -> bool {
+    match manager.register(hotkey) {
+        Ok(()) => true,
+        // the x server refuses to grab keys that are already grabbed by another application
+        Err(
+            global_hotkey::Error::AlreadyRegistered(_) | global_hotkey::Error::FailedToRegister(_),
+        ) => {
+            log::error!(
+                "Hotkey {keybind} is already in use by another application. Change `{option}` in the config to use another one."
+            );
+            false
+        }
+        Err(e) => {
+            log::error!("failed to register the hotkey {keybind}: {e}");
+            false
+        }
+    }
+}
+
+/// Spawns a copy of luma without the `--daemonize` flag in a new session, detached from the
+/// controlling terminal, so it keeps running after the parent exits. Returns if that worked.
+#[cfg(unix)]
+fn daemonize() -> bool {
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+
+    let exe = match std::env::current_exe() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("failed to daemonize: {e}");
+            return false;
+        }
+    };
+    let mut cmd = Command::new(exe);
+    cmd.args(std::env::args_os().skip(1).filter(|v| v != "--daemonize"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // SAFETY: setsid is async-signal-safe. the forked child isn't a process group leader, so it
+    // can always start a new session.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    match cmd.spawn() {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("failed to daemonize: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn daemonize() -> bool {
+    eprintln!("--daemonize is not supported on this platform");
+    false
+}
+
+/// the query passed with `--query <text>` or `--query=<text>`, which the window is opened with
+fn query_arg() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--query" {
+            return args.next();
+        }
+        if let Some(query) = arg.strip_prefix("--query=") {
+            return Some(query.to_string());
+        }
+    }
+    None
+}
+
 fn main() -> iced::Result {
+    if std::env::args_os().skip(1).any(|v| v == "--daemonize") {
+        // the logs will still be written to the log file by the daemonized process.
+        if !daemonize() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if std::env::args_os().skip(1).any(|v| v == "--config-schema") {
+        let schema = schemars::schema_for!(Config);
+        match serde_json::to_string_pretty(&schema) {
+            Ok(v) => println!("{v}"),
+            Err(e) => eprintln!("failed to generate the config schema: {e}"),
+        }
+        return Ok(());
+    }
     logging::init();
     log::info!("--- New Run ---");
+    utils::warn_if_no_home_dir();
     let Some(config) = load_config() else {
         return Ok(());
     };
     let config = Arc::new(config);
+    utils::set_autostart(config.autostart);
+    logging::apply_config(&config);
     let Some(hotkey) =
         keybind::key_and_modifiers_from_str(&config.keybind).and_then(keybind::iced_to_hotkey)
     else {
@@ -1213,7 +2250,8 @@ fn main() -> iced::Result {
         );
         return Ok(());
     };
-    let (sqlite, sqlite_deinitializer) = sqlite::init().expect("failed to initialize sqlite");
+    let (sqlite, sqlite_deinitializer) =
+        sqlite::init(config.sqlite_wal).expect("failed to initialize sqlite");
     let lua = match lua::setup_runtime() {
         Ok(v) => v,
         Err(e) => {
@@ -1222,12 +2260,17 @@ fn main() -> iced::Result {
         }
     };
     let manager = GlobalHotKeyManager::new().expect("failed to start the hotkey manager");
-    manager
-        .register(hotkey)
-        .expect("failed to register the hotkey");
+    let hotkey = register_hotkey(&manager, hotkey, &config.keybind, "keybind").then_some(hotkey);
+    let paste_hotkey =
+        paste_hotkey(&config).filter(|&v| register_paste_hotkey(&manager, v, &config));
     let manager = Arc::new(manager);
     let message_sender = MessageSender::new();
     let message_sender_subscription = message_sender.clone();
+    #[cfg(target_os = "linux")]
+    if config.tray {
+        tray::spawn(message_sender.clone());
+    }
+    let initial_query = query_arg();
 
     iced::daemon(
         move || {
@@ -1241,32 +2284,56 @@ fn main() -> iced::Result {
                 window: None,
                 plugins: Vec::new(),
                 plugin_builder: Vec::new(),
-                theme: Theme::Dracula,
                 index_sender: None,
                 collector_controller: None,
                 showing_actions: false,
                 selected_action: 0,
+                resized_height: None,
+                hovered_entry: None,
+                last_error_popup: None,
+                popup_deadlines: HashMap::new(),
                 special_windows: BTreeMap::new(),
                 lua: lua.clone(),
+                lua_plugins: Vec::new(),
                 context: Context {
-                    http_cache: Arc::new(HTTPCache::new().into()),
+                    http_cache: Arc::new(HTTPCache::new(&config.http).into()),
                     file_index: Arc::new(RwLock::new(FileIndex::new())),
                     sqlite: sqlite.clone(),
                     message_sender: message_sender.clone(),
                     config: config.clone(),
+                    registered_plugins: Arc::new([]),
+                    clipboard_history: Arc::default(),
+                    theme: Theme::Dracula,
                 },
                 hotkey,
+                paste_hotkey,
                 manager: manager.clone(),
                 initializing_plugins: Vec::new(),
+                init_cancelled: Arc::default(),
                 plugin_configs: HashMap::new(),
+                last_action: None,
+                navigation_held_since: None,
+                completions: None,
+                active_prefix: None,
             };
             state.add_plugin::<ControlPlugin>();
+            state.add_plugin::<HelpPlugin>();
+            state.add_plugin::<HistoryPlugin>();
             state.add_plugin::<ThemePlugin>();
             state.add_plugin::<DicePlugin>();
             state.add_plugin::<FendPlugin>();
+            state.add_plugin::<DatePlugin>();
             state.add_plugin::<RunPlugin>();
+            state.add_plugin::<PathPlugin>();
+            state.add_plugin::<ProjectsPlugin>();
             state.add_lua_plugins();
+            state.add_native_plugins();
             state.add_plugin::<FilePlugin>();
+            state.context.registered_plugins = state
+                .plugin_builder
+                .iter_mut()
+                .map(|(id, builder)| (id.clone(), builder().any_description().to_string().into()))
+                .collect();
             let focus_task = text_input::focus(text_input_id);
             let http_cache = state.context.http_cache.clone();
             let sqlite = sqlite.clone();
@@ -1274,19 +2341,35 @@ fn main() -> iced::Result {
                 async move { http_cache.read().await.init(sqlite).await },
                 |_| Message::None,
             );
-            (state, Task::batch([focus_task, http_cache_init_task]))
+            let show_task = match initial_query.clone() {
+                Some(query) => {
+                    Task::done(Message::Show).chain(Task::done(Message::SetSearch(query)))
+                }
+                None => Task::none(),
+            };
+            (
+                state,
+                Task::batch([focus_task, http_cache_init_task, show_task]),
+            )
         },
         daemon_update,
         daemon_view,
     )
-    .theme(|s, _| s.theme.clone())
-    .subscription(move |_| {
+    .theme(|s, _| s.context.theme.clone())
+    .subscription(move |state| {
         Subscription::batch([
             window::events().map(|ev| match ev.1 {
                 window::Event::Unfocused => Message::Blurred(ev.0),
                 window::Event::Closed => Message::Hide(ev.0),
+                window::Event::Resized(size) => Message::Resized(ev.0, size),
                 _ => Message::None,
             }),
+            iced::event::listen_with(|ev, _, id| match ev {
+                iced::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                    Some(Message::CursorMoved(id))
+                }
+                _ => None,
+            }),
             hotkey_sub().map(Message::HotkeyPressed),
             Subscription::run(file_index::file_index_service).map(Message::IndexerMessage),
             Subscription::run(filter_service::collector).map(Message::CollectorMessage),
@@ -1299,6 +2382,12 @@ fn main() -> iced::Result {
             }),
             cache_clear_sub(),
             watch_config(),
+            watch_applications(),
+            if state.context.config.watch_clipboard {
+                watch_clipboard()
+            } else {
+                Subscription::none()
+            },
             Subscription::run_with(message_sender_subscription.clone(), message_sender_handler),
         ])
     })
@@ -1359,6 +2448,89 @@ fn cache_clear_sub() -> Subscription<Message> {
     })
 }
 
+/// invalidates cached desktop files when they change and tells the run plugin about it, so
+/// installed and removed applications show up without waiting for the cache to expire
+fn watch_applications() -> Subscription<Message> {
+    Subscription::run(|| {
+        channel(32, |mut output: Sender<_>| async move {
+            let (sender, mut receiver) = unbounded_channel();
+            let watcher = notify::recommended_watcher(move |ev: Result<notify::Event, _>| {
+                let Ok(ev) = ev else { return };
+                if !matches!(
+                    ev.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    return;
+                }
+                let Ok(mut cache) = utils::DESKTOP_FILE_INFO_CACHE.write() else {
+                    return;
+                };
+                let mut changed = false;
+                for path in &ev.paths {
+                    if path.extension() == Some(OsStr::new("desktop")) {
+                        cache.invalidate(path.as_path());
+                        changed = true;
+                    }
+                }
+                if changed {
+                    _ = sender.send(());
+                }
+            });
+            let mut watcher = match watcher {
+                Ok(v) => v,
+                Err(e) => {
+                    log::error!("failed to watch the application directories: {e}");
+                    return;
+                }
+            };
+            for dir in utils::APPLICATION_DIRS.iter().filter(|v| v.is_dir()) {
+                if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                    log::debug!("failed to watch {}: {e}", dir.display());
+                }
+            }
+            while receiver.recv().await.is_some() {
+                // package managers usually change multiple files at once
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                while receiver.try_recv().is_ok() {}
+                if output.send(Message::ApplicationsChanged).await.is_err() {
+                    break;
+                }
+            }
+            drop(watcher);
+        })
+    })
+}
+
+/// how many copied texts are kept in [`Context::clipboard_history`]
+const CLIPBOARD_HISTORY_LEN: usize = 100;
+
+fn watch_clipboard() -> Subscription<Message> {
+    Subscription::run(|| {
+        channel(32, |mut output: Sender<_>| async move {
+            let (sender, mut receiver) = unbounded_channel();
+            // the watchers block, so they get their own thread
+            std::thread::spawn(move || {
+                let Some(mut watcher) = clipboard_watcher::watcher() else {
+                    return;
+                };
+                while let Some(text) = watcher.next() {
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if sender.send(text).is_err() {
+                        break;
+                    }
+                }
+            });
+            while let Some(text) = receiver.recv().await {
+                _ = output
+                    .send(Message::ClipboardChanged(text.into()))
+                    .await;
+            }
+        })
+    })
+}
+
 fn watch_config() -> Subscription<Message> {
     Subscription::run(|| {
         channel(32, |mut output: Sender<_>| async move {
@@ -1,7 +1,12 @@
-#[derive(Debug)]
+use std::borrow::Cow;
+
+#[derive(Debug, Clone)]
 pub struct MatcherInput {
     split_words: Vec<String>,
+    /// the lowercase query
     input: String,
+    /// the query as it was typed, for plugins where the case matters
+    original: String,
     has_prefix: bool,
 }
 
@@ -34,6 +39,40 @@ fn is_terminator(c: char) -> bool {
     )
 }
 
+/// lowercases the input, without allocating if it already is lowercase. the search bar lowercases
+/// its query, so this only has to check it.
+fn to_lowercase(s: &str) -> Cow<'_, str> {
+    let is_lowercase = if s.is_ascii() {
+        !s.bytes().any(|c| c.is_ascii_uppercase())
+    } else {
+        s.chars().all(|c| c.to_lowercase().eq([c]))
+    };
+    if is_lowercase {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.to_lowercase())
+    }
+}
+
+/// splits a lowercase string into the words it's matched by
+fn split_words(s: &str) -> impl Iterator<Item = &str> {
+    s.split_terminator(is_terminator)
+        .map(|v| v.trim_matches(is_terminator))
+        .filter(|v| !v.is_empty())
+}
+
+/// how strictly the input has to match a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchMode {
+    /// the words appear in the pattern in order, each one anywhere inside one of its words
+    #[default]
+    Subsequence,
+    /// every word is the start of a word of the pattern, in order
+    Prefix,
+    /// the words are exactly the words of the pattern
+    Exact,
+}
+
 macro_rules! _try {
     ($expr:expr) => {
         match $expr {
@@ -48,26 +87,62 @@ impl MatcherInput {
         if s.is_empty() {
             return Self {
                 split_words: Vec::new(),
-                input: s,
+                input: String::new(),
+                original: s,
                 has_prefix,
             };
         }
+        let input = to_lowercase(&s).into_owned();
         Self {
-            split_words: s
-                .split_terminator(is_terminator)
-                .map(|v| v.trim_matches(is_terminator))
-                .filter(|v| !v.is_empty())
-                .map(str::to_string)
-                .collect(),
-            input: s,
+            split_words: split_words(&input).map(str::to_string).collect(),
+            input,
+            original: s,
             has_prefix,
         }
     }
 
+    /// replaces the input while reusing the allocations of this one. if the new input is this
+    /// input with a single character appended, the words are updated instead of being split again.
+    pub fn set_input(&mut self, s: &str, has_prefix: bool) {
+        self.has_prefix = has_prefix;
+        if s == self.original {
+            return;
+        }
+        let appended = s
+            .strip_prefix(self.original.as_str())
+            .filter(|rest| rest.len() == 1 && rest.is_ascii() && self.original.is_ascii())
+            .and_then(|rest| rest.chars().next());
+        match appended {
+            Some(c) if is_terminator(c) => {}
+            Some(c) if self.input.ends_with(is_terminator) || self.input.is_empty() => {
+                self.split_words.push(c.to_ascii_lowercase().to_string());
+            }
+            Some(c) => match self.split_words.last_mut() {
+                Some(word) => word.push(c.to_ascii_lowercase()),
+                None => self.split_words.push(c.to_ascii_lowercase().to_string()),
+            },
+            None => {
+                self.split_words.clear();
+                self.split_words
+                    .extend(split_words(&to_lowercase(s)).map(str::to_string));
+            }
+        }
+        self.original.clear();
+        self.original.push_str(s);
+        self.input.clear();
+        self.input.push_str(&to_lowercase(s));
+    }
+
+    /// the query in lowercase
     pub fn input(&self) -> &str {
         &self.input
     }
 
+    /// the query in the case it was typed in
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
     pub fn has_prefix(&self) -> bool {
         self.has_prefix
     }
@@ -76,15 +151,46 @@ impl MatcherInput {
         matches_words(pattern, &self.split_words).is_matching()
     }
 
+    /// like [`Self::matches`], with the strictness picked by the plugin. plugins with a small
+    /// list of fixed names usually want [`MatchMode::Prefix`] to avoid odd fuzzy hits.
+    pub fn matches_with(&self, pattern: &str, mode: MatchMode) -> bool {
+        if self.split_words.is_empty() {
+            return self.matches(pattern);
+        }
+        let pattern = pattern.to_lowercase();
+        let mut pattern_words = split_words(&pattern);
+        match mode {
+            MatchMode::Subsequence => self.matches(&pattern),
+            MatchMode::Prefix => self.split_words.iter().all(|word| {
+                pattern_words.any(|pattern_word| pattern_word.starts_with(word.as_str()))
+            }),
+            MatchMode::Exact => pattern_words.eq(self.split_words.iter().map(String::as_str)),
+        }
+    }
+
     pub fn matches_perfect(&self, pattern: &str) -> Option<bool> {
         let res = matches_words(pattern, &self.split_words);
         res.is_matching()
             .then_some(matches!(res, MatchResult::PerfectMatch))
     }
 
+    /// like [`Self::matches_perfect`], but also returns the byte offset in `pattern` at which
+    /// the first word matched, so plugins can rank matches near the start higher.
+    pub fn matches_perfect_at(&self, pattern: &str) -> Option<(bool, usize)> {
+        let (res, start) = matches_words_at(pattern, &self.split_words);
+        res.is_matching()
+            .then_some((matches!(res, MatchResult::PerfectMatch), start))
+    }
+
     pub fn words(&self) -> &[String] {
         &self.split_words
     }
+
+    /// if there's nothing to match, e.g. because the query is only the prefix of the plugin or
+    /// made up of separators. nothing but empty patterns matches an empty input.
+    pub fn is_empty(&self) -> bool {
+        self.split_words.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -115,60 +221,89 @@ impl MatchResult {
     }
 }
 
-fn matches_words(pattern: &str, mut words: &[impl AsRef<str>]) -> MatchResult {
+fn matches_words(pattern: &str, words: &[impl AsRef<str>]) -> MatchResult {
+    matches_words_at(pattern, words).0
+}
+
+/// matches the words against the pattern, returning the result and where the match of the first
+/// word starts.
+fn matches_words_at(pattern: &str, words: &[impl AsRef<str>]) -> (MatchResult, usize) {
     if words.is_empty() {
-        return MatchResult::from_match(pattern.trim().is_empty());
+        // see `MatcherInput::is_empty`
+        let res = if pattern.trim().is_empty() {
+            MatchResult::PerfectMatch
+        } else {
+            MatchResult::DoesNotMatch
+        };
+        return (res, 0);
+    }
+    // every byte of the words has to match a different byte of the pattern
+    if words.iter().map(|v| v.as_ref().len()).sum::<usize>() > pattern.len() {
+        return (MatchResult::DoesNotMatch, 0);
     }
-    let mut current_str: &str = words[0].as_ref();
+    let mut words = words.iter().map(|v| v.as_ref().as_bytes());
+    // this works on bytes instead of chars, which gives the same results: terminators are ascii,
+    // and a multibyte char can only match the same char, as continuation bytes never equal the
+    // first byte of a char.
+    let Some(mut current_str) = words.next() else {
+        unreachable!("words is not empty")
+    };
     let mut last_current_str = current_str;
-    words = &words[1..];
     let mut last_terminator = true;
     let last_i_perfect_val = pattern.len().saturating_sub(1);
+    let mut first_word = true;
+    let mut start = 0;
 
     let mut perfect = true;
-    for (i, c) in pattern.char_indices() {
-        if current_str.is_empty() {
-            if is_terminator(c) {
-                if last_terminator {
-                    continue;
-                }
-                if words.is_empty() {
-                    return MatchResult::new(
-                        current_str.is_empty(),
-                        i == last_i_perfect_val && perfect,
-                    );
-                }
-                last_terminator = true;
-                current_str = words[0].as_ref();
-                last_current_str = current_str;
-                words = &words[1..];
+    for (i, c) in pattern.bytes().enumerate() {
+        let terminator = is_terminator(char::from(c));
+        if let [next, rest @ ..] = current_str {
+            if terminator {
+                current_str = last_current_str;
             } else {
-                perfect = false;
-            }
-        } else if is_terminator(c) {
-            current_str = last_current_str;
-        } else {
-            last_terminator = false;
-            let next_char = current_str.chars().next();
-            if let Some(next) = next_char {
-                if c.to_ascii_lowercase() == next {
-                    current_str = &current_str[next.len_utf8()..];
+                last_terminator = false;
+                if c.to_ascii_lowercase() == *next {
+                    if first_word && current_str.len() == last_current_str.len() {
+                        start = i;
+                    }
+                    current_str = rest;
                 } else {
                     perfect = false;
                     current_str = last_current_str;
                 }
-            } else {
-                perfect = false;
             }
+        } else if terminator {
+            if last_terminator {
+                continue;
+            }
+            let Some(next_word) = words.next() else {
+                return (
+                    MatchResult::from_match(i == last_i_perfect_val && perfect),
+                    start,
+                );
+            };
+            first_word = false;
+            last_terminator = true;
+            current_str = next_word;
+            last_current_str = current_str;
+        } else {
+            perfect = false;
         }
     }
 
-    MatchResult::new(words.is_empty() && current_str.is_empty(), perfect)
+    (
+        MatchResult::new(words.next().is_none() && current_str.is_empty(), perfect),
+        start,
+    )
 }
 
 #[cfg(test)]
 mod test {
-    use crate::matcher::{MatchResult, matches_words};
+    use std::borrow::Cow;
+
+    use crate::matcher::{
+        MatchMode, MatchResult, MatcherInput, matches_words, matches_words_at, to_lowercase,
+    };
 
     #[test]
     fn test() {
@@ -200,4 +335,52 @@ mod test {
         );
         assert_eq!(MatchResult::DoesNotMatch, matches_words("quit", &["qut"]));
     }
+
+    #[test]
+    fn start() {
+        assert_eq!(0, matches_words_at("config.toml", &["conf"]).1);
+        assert_eq!(2, matches_words_at("myconf_backup.toml", &["conf"]).1);
+        assert_eq!(4, matches_words_at("convert_plugin.rs", &["er", "rs"]).1);
+    }
+
+    #[test]
+    fn modes() {
+        let input = MatcherInput::new("dis fi".to_string(), false);
+        assert!(input.matches_with("disable file", MatchMode::Prefix));
+        assert!(!input.matches_with("disable profile", MatchMode::Prefix));
+        assert!(input.matches_with("disable profile", MatchMode::Subsequence));
+        assert!(!input.matches_with("disable file", MatchMode::Exact));
+        let input = MatcherInput::new("Disable File".to_string(), false);
+        assert!(input.matches_with("disable-file", MatchMode::Exact));
+        assert!(!input.matches_with("disable file now", MatchMode::Exact));
+    }
+
+    #[test]
+    fn set_input() {
+        let mut input = MatcherInput::new(String::new(), false);
+        for query in ["l", "lu", "lum", "lum ", "lum d", "lum de", "Lum dev", "lu", "", "a.b"] {
+            input.set_input(query, false);
+            assert_eq!(input.words(), MatcherInput::new(query.to_string(), false).words());
+            assert_eq!(input.original(), query);
+            assert_eq!(input.input(), query.to_lowercase());
+        }
+    }
+
+    #[test]
+    fn empty() {
+        for query in ["", "-", " _ "] {
+            let input = MatcherInput::new(query.to_string(), true);
+            assert!(input.is_empty(), "{query:?}");
+            assert!(!input.matches("luma"));
+        }
+        assert!(!MatcherInput::new("lu".to_string(), true).is_empty());
+    }
+
+    #[test]
+    fn lowercase() {
+        assert!(matches!(to_lowercase("lum dev"), Cow::Borrowed("lum dev")));
+        assert!(matches!(to_lowercase("größe"), Cow::Borrowed("größe")));
+        assert_eq!(to_lowercase("Lum Dev"), "lum dev");
+        assert_eq!(to_lowercase("GRÖSSE"), "grösse");
+    }
 }
@@ -0,0 +1,318 @@
+// Loader for native plugins: shared libraries in the plugin directory that export a C-ABI entry
+// point returning a `PluginRegistration`.
+
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use iced::Task;
+use libloading::Library;
+
+use crate::{
+    Action, CustomData, Entry, Message, Plugin, PluginContext, ResultBuilderRef,
+    config::PluginSettings, keybind, matcher::MatcherInput, plugin::InstancePlugin, utils,
+};
+
+/// the version of the registration api. it is bumped whenever the layout of any of the
+/// `#[repr(C)]` types below changes, and plugins built against another version are not loaded.
+pub const ABI_VERSION: u32 = 1;
+
+/// the symbol every native plugin exports, with the signature of [`EntryPoint`]
+const ENTRY_POINT: &[u8] = b"luma_plugin_entry\0";
+
+pub static NATIVE_PLUGIN_DIR: LazyLock<PathBuf> =
+    LazyLock::new(|| utils::CONFIG_DIR.join("plugins"));
+
+/// an action of a native plugin. `shortcut` may be null, otherwise it's a keybind such as
+/// `Ctrl+Enter`.
+#[repr(C)]
+pub struct NativeAction {
+    pub name: *const c_char,
+    pub id: *const c_char,
+    pub shortcut: *const c_char,
+}
+
+/// a result of a native plugin. `id` is passed back to `handle` when an action is run on it.
+#[repr(C)]
+pub struct NativeEntry {
+    pub name: *const c_char,
+    pub subtitle: *const c_char,
+    pub id: u64,
+    pub perfect_match: bool,
+    pub weight: i32,
+}
+
+/// adds a result, the strings of the entry are copied.
+pub type AddEntryFn = extern "C" fn(sink: *mut c_void, entry: *const NativeEntry);
+
+/// describes a native plugin. all strings are nul-terminated utf-8 and have to stay valid until
+/// `drop` is called. the functions can be called from multiple threads at once.
+///
+/// a panic or exception can't cross the library boundary without aborting luma, so plugins have
+/// to catch them and return `false` instead. a plugin that failed is not called again.
+#[repr(C)]
+pub struct PluginRegistration {
+    /// has to be [`ABI_VERSION`]
+    pub abi_version: u32,
+    pub prefix: *const c_char,
+    pub description: *const c_char,
+    pub actions: *const NativeAction,
+    pub actions_len: usize,
+    /// passed to all of the functions below
+    pub data: *mut c_void,
+    /// calls `add_entry` with `sink` for every result of `query`. returns if it succeeded.
+    pub get_for_values: extern "C" fn(
+        data: *mut c_void,
+        query: *const c_char,
+        has_prefix: bool,
+        sink: *mut c_void,
+        add_entry: AddEntryFn,
+    ) -> bool,
+    /// runs the action with the id `action` on the entry with the id `entry`. returns if it
+    /// succeeded.
+    pub handle: extern "C" fn(data: *mut c_void, entry: u64, action: *const c_char) -> bool,
+    /// frees `data` and the registration. nothing is called after this.
+    pub drop: extern "C" fn(data: *mut c_void),
+}
+
+pub type EntryPoint = unsafe extern "C" fn() -> *const PluginRegistration;
+
+/// # Safety
+///
+/// `ptr` has to be null or a valid nul-terminated string
+unsafe fn string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: guaranteed by the caller
+    Some(
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+extern "C" fn add_entry(sink: *mut c_void, entry: *const NativeEntry) {
+    if entry.is_null() {
+        return;
+    }
+    // SAFETY: `sink` is the vec passed to `get_for_values`, and the plugin has to pass a valid
+    // entry.
+    let (entries, entry) = unsafe { (&mut *sink.cast::<Vec<Entry>>(), &*entry) };
+    // SAFETY: the strings of an entry have to be valid
+    let (name, subtitle) = unsafe { (string(entry.name), string(entry.subtitle)) };
+    entries.push(
+        Entry::new(
+            name.unwrap_or_default(),
+            subtitle.unwrap_or_default(),
+            CustomData::new(entry.id),
+        )
+        .perfect(entry.perfect_match)
+        .weight(entry.weight),
+    );
+}
+
+struct LoadedPlugin {
+    registration: *const PluginRegistration,
+    prefix: Arc<str>,
+    description: Arc<str>,
+    actions: Arc<[Action]>,
+    /// set once the plugin reported a failure
+    failed: AtomicBool,
+    // has to be dropped after the registration, which the `Drop` impl takes care of.
+    _library: Library,
+}
+
+// SAFETY: native plugins have to be thread safe
+unsafe impl Send for LoadedPlugin {}
+// SAFETY: see above
+unsafe impl Sync for LoadedPlugin {}
+
+impl LoadedPlugin {
+    fn registration(&self) -> &PluginRegistration {
+        // SAFETY: the registration stays valid until it's dropped
+        unsafe { &*self.registration }
+    }
+
+    /// runs `f` unless the plugin failed before, disabling the plugin if `f` fails
+    fn call(&self, f: impl FnOnce(&PluginRegistration) -> bool) {
+        if self.failed.load(Ordering::Relaxed) {
+            return;
+        }
+        if !f(self.registration()) {
+            self.failed.store(true, Ordering::Relaxed);
+            log::error!("native plugin `{}` failed and was disabled", self.prefix);
+        }
+    }
+
+    fn get_for_values(&self, query: &CStr, has_prefix: bool) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        self.call(|registration| {
+            (registration.get_for_values)(
+                registration.data,
+                query.as_ptr(),
+                has_prefix,
+                (&raw mut entries).cast(),
+                add_entry,
+            )
+        });
+        entries
+    }
+
+    fn handle(&self, entry: u64, action: &CStr) {
+        self.call(|registration| (registration.handle)(registration.data, entry, action.as_ptr()));
+    }
+}
+
+impl Drop for LoadedPlugin {
+    fn drop(&mut self) {
+        let registration = self.registration();
+        (registration.drop)(registration.data);
+    }
+}
+
+/// reads the prefix, description and actions of the registration
+fn read_registration(plugin: &mut LoadedPlugin) -> Result<(), String> {
+    // SAFETY: the registration stays valid until it's dropped
+    let reg = unsafe { &*plugin.registration };
+    // SAFETY: the strings of the registration have to be valid
+    let Some(prefix) = (unsafe { string(reg.prefix) }) else {
+        return Err("the plugin has no prefix".into());
+    };
+    // SAFETY: see above
+    let description = unsafe { string(reg.description) }.unwrap_or_default();
+    let native_actions = if reg.actions.is_null() {
+        &[]
+    } else {
+        // SAFETY: `actions` points to `actions_len` actions
+        unsafe { std::slice::from_raw_parts(reg.actions, reg.actions_len) }
+    };
+    let mut actions = Vec::with_capacity(native_actions.len());
+    for action in native_actions {
+        // SAFETY: the strings of the actions have to be valid
+        let (name, id, shortcut) = unsafe {
+            (
+                string(action.name),
+                string(action.id),
+                string(action.shortcut),
+            )
+        };
+        let name = name.unwrap_or_default();
+        let id = id.unwrap_or_default();
+        let action = match shortcut {
+            None if actions.is_empty() => Action::default_owned(name, id),
+            None => Action::without_shortcut_owned(name, id),
+            Some(shortcut) => match keybind::key_and_modifiers_from_str(&shortcut) {
+                Some(shortcut) => Action::new_owned(name, id, shortcut),
+                None => return Err(format!("{shortcut:?} is not a valid keybind")),
+            },
+        };
+        actions.push(action);
+    }
+    if actions.is_empty() {
+        actions.push(Action::default("Default Action", ""));
+    }
+    plugin.prefix = prefix.into();
+    plugin.description = description.into();
+    plugin.actions = actions.into();
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct NativePlugin(Arc<LoadedPlugin>);
+
+/// loads the native plugin at `path`
+pub fn load_native_plugin(path: &Path) -> Result<NativePlugin, String> {
+    // SAFETY: loading a library runs its initializers, which is as safe as the plugin is. the
+    // readme warns about only running trusted plugins.
+    let library = unsafe { Library::new(path) }.map_err(|e| e.to_string())?;
+    // SAFETY: the entry point has to have this signature
+    let entry_point = unsafe { library.get::<EntryPoint>(ENTRY_POINT) }
+        .map_err(|e| e.to_string())
+        .map(|v| *v)?;
+    // SAFETY: see above
+    let registration = unsafe { entry_point() };
+    if registration.is_null() {
+        return Err("the entry point returned null".into());
+    }
+    // SAFETY: the registration is valid until it's dropped
+    let reg = unsafe { &*registration };
+    if reg.abi_version != ABI_VERSION {
+        // the layout of the rest of the registration is unknown, so it can't be dropped either
+        return Err(format!(
+            "the plugin was built for version {} of the plugin api, but luma uses version {ABI_VERSION}",
+            reg.abi_version
+        ));
+    }
+    let mut plugin = LoadedPlugin {
+        registration,
+        prefix: Arc::from(""),
+        description: Arc::from(""),
+        actions: Arc::new([]),
+        failed: AtomicBool::new(false),
+        _library: library,
+    };
+    // the plugin is dropped properly if the registration is invalid
+    read_registration(&mut plugin)?;
+    Ok(NativePlugin(Arc::new(plugin)))
+}
+
+impl InstancePlugin for NativePlugin {
+    fn config(&mut self) -> Option<PluginSettings> {
+        None
+    }
+}
+
+impl Plugin for NativePlugin {
+    fn prefix(&self) -> &str {
+        &self.0.prefix
+    }
+
+    fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    fn actions(&self) -> &[Action] {
+        &self.0.actions
+    }
+
+    async fn get_for_values(
+        &self,
+        input: &MatcherInput,
+        builder: ResultBuilderRef<'_>,
+        _: PluginContext<'_>,
+    ) {
+        let Ok(query) = CString::new(input.input()) else {
+            return;
+        };
+        // native plugins are synchronous, so they're run on the blocking thread pool instead of
+        // holding up the collector and the other plugins
+        let plugin = self.0.clone();
+        let has_prefix = input.has_prefix();
+        let entries =
+            tokio::task::spawn_blocking(move || plugin.get_for_values(&query, has_prefix)).await;
+        let entries = match entries {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("native plugin `{}` failed: {e}", self.0.prefix);
+                return;
+            }
+        };
+        builder.commit(entries.into_iter()).await;
+    }
+
+    async fn init(&mut self, _: PluginContext<'_>) {}
+
+    fn handle_pre(&self, thing: CustomData, action: &str, _: PluginContext<'_>) -> Task<Message> {
+        let Ok(action) = CString::new(action) else {
+            return Task::none();
+        };
+        self.0.handle(thing.into::<u64>(), &action);
+        Task::none()
+    }
+}
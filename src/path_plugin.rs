@@ -0,0 +1,173 @@
+// Plugin to run the executables in $PATH
+
+use std::{
+    collections::HashSet,
+    fs::Metadata,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use iced::{
+    Task,
+    keyboard::{Key, Modifiers, key::Named},
+};
+
+use crate::{
+    Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef, StructPlugin,
+    matcher::MatcherInput, utils,
+};
+
+struct Executable {
+    name: Arc<str>,
+    dir: Arc<Path>,
+}
+
+struct Cache {
+    /// the directories that were read, $PATH and the configured extra directories
+    dirs: Vec<PathBuf>,
+    /// the modification times of the directories in $PATH when they were read
+    modified: Vec<Option<SystemTime>>,
+    executables: Arc<[Executable]>,
+}
+
+/// plugins are recreated every time the window opens, so the executables are cached here and only
+/// read again when a directory in $PATH changed.
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+#[derive(Default)]
+pub struct PathPlugin {
+    executables: Arc<[Executable]>,
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &Metadata) -> bool {
+    true
+}
+
+async fn read_executables(dirs: &[PathBuf]) -> Vec<Executable> {
+    let mut executables = Vec::new();
+    let mut names = HashSet::new();
+    for dir in dirs {
+        let Ok(mut dirent) = tokio::fs::read_dir(dir).await else {
+            continue;
+        };
+        let dir: Arc<Path> = dir.as_path().into();
+        while let Ok(Some(entry)) = dirent.next_entry().await {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            // the first directory in $PATH containing an executable is the one that's run
+            if names.contains(&name) {
+                continue;
+            }
+            let Ok(metadata) = tokio::fs::metadata(entry.path()).await else {
+                continue;
+            };
+            if !metadata.is_file() || !is_executable(&metadata) {
+                continue;
+            }
+            executables.push(Executable {
+                name: name.as_str().into(),
+                dir: dir.clone(),
+            });
+            names.insert(name);
+        }
+    }
+    executables.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    executables
+}
+
+impl StructPlugin for PathPlugin {
+    fn prefix() -> &'static str {
+        "bin"
+    }
+
+    fn description() -> &'static str {
+        "Run the executables in your $PATH"
+    }
+
+    async fn get_for_values(
+        &self,
+        input: &MatcherInput,
+        builder: ResultBuilderRef<'_>,
+        _: PluginContext<'_>,
+    ) {
+        let iter = self
+            .executables
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| {
+                input.matches_perfect(&v.name).map(|perfect| {
+                    Entry::new(v.name.clone(), v.dir.clone(), CustomData::new(i)).perfect(perfect)
+                })
+            });
+        builder.commit(iter).await;
+    }
+
+    async fn init(&mut self, context: PluginContext<'_>) {
+        let dirs = context
+            .global_config
+            .executable_dirs()
+            .map(Path::to_path_buf)
+            .collect::<Vec<_>>();
+        let mut modified = Vec::with_capacity(dirs.len());
+        for dir in &dirs {
+            modified.push(
+                tokio::fs::metadata(dir)
+                    .await
+                    .and_then(|v| v.modified())
+                    .ok(),
+            );
+        }
+        let cached = CACHE
+            .lock()
+            .expect("failed to lock the executable cache")
+            .as_ref()
+            .filter(|cache| cache.dirs == dirs && cache.modified == modified)
+            .map(|cache| cache.executables.clone());
+        if let Some(executables) = cached {
+            self.executables = executables;
+            return;
+        }
+        log::debug!("reading the executables in $PATH");
+        self.executables = read_executables(&dirs).await.into();
+        *CACHE.lock().expect("failed to lock the executable cache") = Some(Cache {
+            dirs,
+            modified,
+            executables: self.executables.clone(),
+        });
+    }
+
+    fn handle_pre(&self, thing: CustomData, action: &str, _: PluginContext<'_>) -> Task<Message> {
+        let executable = &self.executables[thing.into::<usize>()];
+        let command = Command::new(executable.dir.join(&*executable.name));
+        if action == "terminal" {
+            utils::run_in_terminal(&command);
+        } else {
+            utils::run_cmd(command);
+        }
+        Task::none()
+    }
+
+    fn actions(&self) -> &'static [Action] {
+        const {
+            &[
+                Action::default("Run", "run"),
+                Action::new(
+                    "Run in terminal",
+                    "terminal",
+                    (Modifiers::CTRL, Key::Named(Named::Enter)),
+                ),
+            ]
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fmt::{Debug, Display};
 use std::hash::{Hash, Hasher};
 use std::ops::{Bound, Deref, Range, RangeBounds};
@@ -6,6 +7,7 @@ use std::sync::Arc;
 
 use iced::Task;
 use iced::futures::future::BoxFuture;
+use iced::widget::svg;
 use rusqlite::ToSql;
 
 use crate::config::PluginSettings;
@@ -94,6 +96,14 @@ impl StringLike {
         }
     }
 
+    /// the whole path this string is a part of, if it was created from a shared path
+    pub fn full_path(&self) -> Option<&Arc<Path>> {
+        match self {
+            StringLike::SharedPath(path, _) => Some(path),
+            _ => None,
+        }
+    }
+
     pub fn substr(&mut self, range: impl RangeBounds<u16>) {
         if matches!(self, StringLike::Empty) {
             return;
@@ -264,11 +274,32 @@ impl<T: std::any::Any + Clone + Send + Sync> CustomDataCompatible for T {
     }
 }
 
+/// plugins that are always enabled, regardless of the config.
+pub const ALWAYS_ENABLED_PLUGINS: &[&str] = &["control", "help"];
+
 pub trait Plugin: Send + Sync {
     fn actions(&self) -> &[Action] {
         const { &[Action::default("Default Action", "")] }
     }
+    /// the actions of a single entry, for plugins whose entries don't all support the same
+    /// actions. defaults to [`Plugin::actions`].
+    #[allow(unused_variables)]
+    fn actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        Cow::Borrowed(self.actions())
+    }
     fn prefix(&self) -> &str;
+    /// the name shown next to the results of the plugin. defaults to the prefix.
+    fn display_name(&self) -> &str {
+        self.prefix()
+    }
+    /// an icon shown in front of the display name
+    fn display_icon(&self) -> Option<svg::Handle> {
+        None
+    }
+    /// a short, one-line description of the plugin, shown in the help list.
+    fn description(&self) -> &str {
+        ""
+    }
     fn get_for_values_arc(
         &self,
         input: Arc<MatcherInput>,
@@ -277,6 +308,10 @@ pub trait Plugin: Send + Sync {
     ) -> impl Future<Output = ()> + Send {
         async move { self.get_for_values(&input, builder, context).await }
     }
+    /// adds the results for the input to the builder. the input has no leading or trailing
+    /// whitespace. if the query started with the prefix of the plugin, the prefix is removed and
+    /// [`MatcherInput::has_prefix`] is set. a query that is only the prefix gives an empty
+    /// input, for which a plugin shows everything, a hint on what to type or nothing.
     fn get_for_values(
         &self,
         input: &MatcherInput,
@@ -297,12 +332,32 @@ pub trait Plugin: Send + Sync {
     ) -> Task<Message> {
         Task::none()
     }
+    /// called once the window was shown and the plugin is initialized, to refresh data that
+    /// changes often. defaults to doing nothing.
+    #[allow(unused_variables)]
+    fn on_show(&self, context: PluginContext) -> Task<Message> {
+        Task::none()
+    }
+    /// called when the window is hidden. defaults to doing nothing.
+    #[allow(unused_variables)]
+    fn on_hide(&self, context: PluginContext) -> Task<Message> {
+        Task::none()
+    }
+    /// the completions the tab key cycles through, for the part of the query after the prefix of
+    /// this plugin. each one replaces that part. only asked when the query starts with the prefix.
+    /// this runs on the ui thread, so it shouldn't wait for the disk or the network.
+    #[allow(unused_variables)]
+    fn completions(&self, query: &str, context: PluginContext) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub struct Entry {
     pub name: StringLike,
     pub subtitle: StringLike,
     pub perfect_match: bool,
+    /// entries with a higher weight are shown above ones with a lower weight. defaults to 0.
+    pub weight: i32,
     pub data: CustomData,
 }
 impl Entry {
@@ -316,6 +371,7 @@ impl Entry {
             subtitle: subtitle.into(),
             data,
             perfect_match: false,
+            weight: 0,
         }
     }
 
@@ -333,6 +389,13 @@ impl Entry {
         self.perfect_match = perfect;
         self
     }
+    /// entries with a higher weight are sorted above entries with a lower one. perfect matches
+    /// are still shown first.
+    #[must_use]
+    pub fn weight(mut self, weight: i32) -> Self {
+        self.weight = weight;
+        self
+    }
 }
 
 pub trait InstancePlugin: Plugin + Clone + 'static {
@@ -344,6 +407,18 @@ impl<T: StructPlugin> Plugin for T {
         Self::prefix()
     }
 
+    fn display_name(&self) -> &str {
+        Self::display_name()
+    }
+
+    fn display_icon(&self) -> Option<svg::Handle> {
+        Self::display_icon()
+    }
+
+    fn description(&self) -> &str {
+        Self::description()
+    }
+
     fn get_for_values(
         &self,
         input: &MatcherInput,
@@ -361,6 +436,10 @@ impl<T: StructPlugin> Plugin for T {
         StructPlugin::actions(self)
     }
 
+    fn actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        StructPlugin::actions_for(self, data)
+    }
+
     fn get_for_values_arc(
         &self,
         input: Arc<MatcherInput>,
@@ -382,9 +461,33 @@ impl<T: StructPlugin> Plugin for T {
     ) -> Task<Message> {
         StructPlugin::handle_post(self, thing, action, context)
     }
+
+    fn on_show(&self, context: PluginContext) -> Task<Message> {
+        StructPlugin::on_show(self, context)
+    }
+
+    fn on_hide(&self, context: PluginContext) -> Task<Message> {
+        StructPlugin::on_hide(self, context)
+    }
+
+    fn completions(&self, query: &str, context: PluginContext) -> Vec<String> {
+        StructPlugin::completions(self, query, context)
+    }
 }
 pub trait StructPlugin: Send + Sync + Default + 'static {
     fn prefix() -> &'static str;
+    /// the name shown next to the results of the plugin. defaults to the prefix.
+    fn display_name() -> &'static str {
+        Self::prefix()
+    }
+    /// an icon shown in front of the display name
+    fn display_icon() -> Option<svg::Handle> {
+        None
+    }
+    /// a short, one-line description of the plugin, shown in the help list.
+    fn description() -> &'static str {
+        ""
+    }
     fn config() -> Option<PluginSettings> {
         None
     }
@@ -392,6 +495,12 @@ pub trait StructPlugin: Send + Sync + Default + 'static {
     fn actions(&self) -> &[Action] {
         const { &[Action::default("Default Action", "")] }
     }
+    /// the actions of a single entry, for plugins whose entries don't all support the same
+    /// actions. defaults to [`StructPlugin::actions`].
+    #[allow(unused_variables)]
+    fn actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        Cow::Borrowed(self.actions())
+    }
     fn get_for_values_arc(
         &self,
         input: Arc<MatcherInput>,
@@ -400,6 +509,7 @@ pub trait StructPlugin: Send + Sync + Default + 'static {
     ) -> impl Future<Output = ()> + Send {
         async move { self.get_for_values(&input, builder, context).await }
     }
+    /// see [`Plugin::get_for_values`]
     fn get_for_values(
         &self,
         input: &MatcherInput,
@@ -420,12 +530,34 @@ pub trait StructPlugin: Send + Sync + Default + 'static {
     ) -> Task<Message> {
         Task::none()
     }
+    /// called once the window was shown and the plugin is initialized, to refresh data that
+    /// changes often. defaults to doing nothing.
+    #[allow(unused_variables)]
+    fn on_show(&self, context: PluginContext) -> Task<Message> {
+        Task::none()
+    }
+    /// called when the window is hidden. defaults to doing nothing.
+    #[allow(unused_variables)]
+    fn on_hide(&self, context: PluginContext) -> Task<Message> {
+        Task::none()
+    }
+    /// the completions the tab key cycles through, for the part of the query after the prefix of
+    /// this plugin. each one replaces that part. only asked when the query starts with the prefix.
+    /// this runs on the ui thread, so it shouldn't wait for the disk or the network.
+    #[allow(unused_variables)]
+    fn completions(&self, query: &str, context: PluginContext) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub trait AnyPlugin: Send + Sync {
     fn as_any_ref(&self) -> &dyn std::any::Any;
     fn any_actions(&self) -> &[Action];
+    fn any_actions_for(&self, data: &CustomData) -> Cow<'_, [Action]>;
     fn any_prefix(&self) -> &str;
+    fn any_display_name(&self) -> &str;
+    fn any_display_icon(&self) -> Option<svg::Handle>;
+    fn any_description(&self) -> &str;
     fn any_get_for_values<'fut>(
         &'fut self,
         input: Arc<MatcherInput>,
@@ -446,6 +578,9 @@ pub trait AnyPlugin: Send + Sync {
         action: &str,
         context: PluginContext,
     ) -> Task<Message>;
+    fn any_on_show(&self, context: PluginContext) -> Task<Message>;
+    fn any_on_hide(&self, context: PluginContext) -> Task<Message>;
+    fn any_completions(&self, query: &str, context: PluginContext) -> Vec<String>;
 }
 impl<T: Plugin + 'static> AnyPlugin for T {
     fn as_any_ref(&self) -> &dyn std::any::Any {
@@ -456,10 +591,26 @@ impl<T: Plugin + 'static> AnyPlugin for T {
         self.actions()
     }
 
+    fn any_actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        self.actions_for(data)
+    }
+
     fn any_prefix(&self) -> &str {
         self.prefix()
     }
 
+    fn any_display_name(&self) -> &str {
+        self.display_name()
+    }
+
+    fn any_display_icon(&self) -> Option<svg::Handle> {
+        self.display_icon()
+    }
+
+    fn any_description(&self) -> &str {
+        self.description()
+    }
+
     fn any_get_for_values<'fut>(
         &'fut self,
         input: Arc<MatcherInput>,
@@ -491,6 +642,15 @@ impl<T: Plugin + 'static> AnyPlugin for T {
     ) -> Task<Message> {
         self.handle_post(thing, action, context)
     }
+    fn any_on_show(&self, context: PluginContext) -> Task<Message> {
+        self.on_show(context)
+    }
+    fn any_on_hide(&self, context: PluginContext) -> Task<Message> {
+        self.on_hide(context)
+    }
+    fn any_completions(&self, query: &str, context: PluginContext) -> Vec<String> {
+        self.completions(query, context)
+    }
 }
 
 impl Debug for CustomData {
@@ -506,11 +666,35 @@ impl Clone for Box<dyn CustomDataCompatible> {
 }
 
 #[derive(Clone)]
-pub struct CustomData(Box<dyn CustomDataCompatible>);
+pub struct CustomData(Data);
+
+#[derive(Clone)]
+enum Data {
+    Value(Box<dyn CustomDataCompatible>),
+    /// built from the name of the entry once it's needed
+    Deferred(fn(&StringLike) -> CustomData),
+}
 
 impl CustomData {
     pub fn new<T: CustomDataCompatible>(value: T) -> Self {
-        Self(Box::new(value))
+        Self(Data::Value(Box::new(value)))
+    }
+
+    /// data that is only built from the name of its entry once the entry is run or its actions
+    /// are shown. this saves an allocation for every entry that's never looked at, which adds up
+    /// for plugins returning thousands of entries.
+    #[must_use]
+    pub fn deferred(build: fn(&StringLike) -> CustomData) -> Self {
+        Self(Data::Deferred(build))
+    }
+
+    /// builds deferred data from the name of its entry
+    #[must_use]
+    pub(crate) fn resolve(self, name: &StringLike) -> Self {
+        match self.0 {
+            Data::Deferred(build) => build(name),
+            data => Self(data),
+        }
     }
 
     /// # Panics
@@ -518,10 +702,31 @@ impl CustomData {
     /// Panics when T is not the same value as the one stored in this [`CustomData`]
     #[must_use]
     pub fn into<T: CustomDataCompatible>(self) -> T {
-        *(self.0 as Box<dyn std::any::Any>)
+        let Data::Value(value) = self.0 else {
+            panic!("deferred data has to be resolved first");
+        };
+        *(value as Box<dyn std::any::Any>)
             .downcast()
             .expect("this should never fail")
     }
+
+    /// returns a reference to the stored value if it is a T
+    #[must_use]
+    pub fn get<T: CustomDataCompatible>(&self) -> Option<&T> {
+        match &self.0 {
+            Data::Value(value) => (&**value as &dyn std::any::Any).downcast_ref(),
+            Data::Deferred(_) => None,
+        }
+    }
+}
+
+/// A registered plugin, as returned by [`PluginContext::plugins`](crate::PluginContext::plugins).
+#[derive(Debug, Clone)]
+pub struct PluginInfo {
+    /// the prefix of the plugin, which is also its id
+    pub prefix: StringLike,
+    pub description: StringLike,
+    pub enabled: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -532,6 +737,7 @@ pub struct GenericEntry {
     pub(crate) plugin: usize,
     pub(crate) data: CustomData,
     pub(crate) perfect_match: bool,
+    pub(crate) weight: i32,
 }
 
 impl GenericEntry {
@@ -547,6 +753,7 @@ impl GenericEntry {
             plugin,
             data,
             perfect_match: false,
+            weight: 0,
         }
     }
 
@@ -555,4 +762,9 @@ impl GenericEntry {
         self.perfect_match = perfect;
         self
     }
+
+    /// the data of this entry, with deferred data built
+    pub(crate) fn data(&self) -> CustomData {
+        self.data.clone().resolve(&self.name)
+    }
 }
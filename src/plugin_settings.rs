@@ -4,7 +4,7 @@ use std::ops::{BitOr, BitOrAssign, Deref, Index};
 use std::sync::{Arc, OnceLock};
 use tokio::sync::{RwLock, RwLockReadGuard};
 
-use mlua::IntoLua;
+use mlua::{FromLua, IntoLua};
 use serde::{Deserialize, Serialize, de::Visitor};
 
 impl<'de> Deserialize<'de> for PluginSettingsValue {
@@ -42,6 +42,18 @@ impl<'de> Deserialize<'de> for PluginSettingsValue {
                 self.visit_i64(i64::from(v))
             }
 
+            // serde_json hands out positive integers as u64
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match i64::try_from(v) {
+                    Ok(v) => self.visit_i64(v),
+                    #[allow(clippy::cast_precision_loss)]
+                    Err(_) => self.visit_f64(v as f64),
+                }
+            }
+
             fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
@@ -206,7 +218,40 @@ impl IntoLua for &PluginSettingsValue {
                 }
                 mlua::Value::Table(table)
             }
-            PSV::Null => todo!(),
+            PSV::Null => mlua::Value::Nil,
+        })
+    }
+}
+
+impl FromLua for PluginSettingsValue {
+    fn from_lua(value: mlua::Value, lua: &mlua::Lua) -> mlua::Result<Self> {
+        use PluginSettingsValue as PSV;
+        Ok(match value {
+            mlua::Value::Nil => PSV::Null,
+            mlua::Value::Boolean(b) => PSV::Boolean(b),
+            mlua::Value::Integer(i) => PSV::Int(i),
+            mlua::Value::Number(n) => PSV::Number(n),
+            mlua::Value::String(s) => PSV::String(s.to_str()?.to_string()),
+            // tables with a sequence part are lists, everything else is a map
+            mlua::Value::Table(table) if table.raw_len() > 0 => PSV::List(
+                table
+                    .sequence_values()
+                    .map(|v| v.and_then(|v| Self::from_lua(v, lua)))
+                    .collect::<mlua::Result<_>>()?,
+            ),
+            mlua::Value::Table(table) => PSV::Map(
+                table
+                    .pairs::<String, mlua::Value>()
+                    .map(|v| v.and_then(|(k, v)| Ok((k.into(), Self::from_lua(v, lua)?))))
+                    .collect::<mlua::Result<_>>()?,
+            ),
+            v => {
+                return Err(mlua::Error::FromLuaConversionError {
+                    from: v.type_name(),
+                    to: "plugin settings value".into(),
+                    message: None,
+                });
+            }
         })
     }
 }
@@ -381,6 +426,17 @@ impl<'de> Deserialize<'de> for PluginSettingsHolder {
     }
 }
 
+/// plugin settings are only known once the plugins are loaded, so the schema accepts any value
+impl schemars::JsonSchema for PluginSettingsHolder {
+    fn schema_name() -> String {
+        "PluginSettings".into()
+    }
+
+    fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        HashMap::<String, serde_json::Value>::json_schema(generator)
+    }
+}
+
 impl Serialize for PluginSettingsHolder {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -0,0 +1,267 @@
+// Plugin to open recently used project folders in an editor
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use iced::{
+    Task,
+    keyboard::{Key, Modifiers, key::Named},
+};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::Deserialize;
+
+use crate::{
+    Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef, StructPlugin,
+    config::PluginSettings, matcher::MatcherInput, utils,
+};
+
+/// the config directories of editors based on vs code, which all store their recently opened
+/// folders the same way.
+const VSCODE_EDITORS: &[&str] = &["Code", "Code - OSS", "VSCodium", "Cursor"];
+
+struct Project {
+    name: Arc<str>,
+    path: Arc<Path>,
+}
+
+struct Cache {
+    /// the modification times of the files the projects were read from
+    modified: Vec<Option<SystemTime>>,
+    projects: Arc<[Project]>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+#[derive(Deserialize)]
+struct ProjectsFile {
+    #[serde(default)]
+    projects: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct ProjectsPlugin {
+    projects: Arc<[Project]>,
+}
+
+/// the files projects are read from: a `projects.toml` in the config directory (containing a
+/// `projects` list) and the recently opened folders of vs code.
+fn project_files() -> Vec<PathBuf> {
+    let mut files = vec![utils::CONFIG_DIR.join("projects.toml")];
+    if let Some(config_home) = utils::CONFIG_DIR.parent() {
+        files.extend(VSCODE_EDITORS.iter().map(|editor| {
+            config_home
+                .join(editor)
+                .join("User")
+                .join("globalStorage")
+                .join("state.vscdb")
+        }));
+    }
+    files
+}
+
+/// decodes a `file://` uri into a path
+fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn read_vscode_recents(file: &Path) -> rusqlite::Result<Vec<PathBuf>> {
+    let connection = Connection::open_with_flags(
+        file,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    let value = connection
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = 'history.recentlyOpenedPathsList'",
+            (),
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+    let value = match serde_json::from_str::<serde_json::Value>(&value) {
+        Ok(v) => v,
+        Err(e) => {
+            log::debug!("failed to parse the recents of {}: {e}", file.display());
+            return Ok(Vec::new());
+        }
+    };
+    Ok(value["entries"]
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| entry["folderUri"].as_str())
+                .filter_map(path_from_uri)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+async fn read_projects_file(file: &Path) -> Vec<PathBuf> {
+    let Ok(contents) = tokio::fs::read_to_string(file).await else {
+        return Vec::new();
+    };
+    match toml::from_str::<ProjectsFile>(&contents) {
+        Ok(v) => v
+            .projects
+            .into_iter()
+            .map(|path| match (path.strip_prefix("~"), &*utils::HOME_DIR) {
+                (Ok(path), Some(home)) => home.join(path),
+                _ => path,
+            })
+            .collect(),
+        Err(e) => {
+            log::error!("failed to read {}: {e}", file.display());
+            Vec::new()
+        }
+    }
+}
+
+async fn read_projects(files: &[PathBuf]) -> Vec<Project> {
+    let mut paths = Vec::new();
+    for file in files {
+        if file.extension().is_some_and(|v| v == "toml") {
+            paths.extend(read_projects_file(file).await);
+        } else if tokio::fs::try_exists(file).await.unwrap_or(false) {
+            match read_vscode_recents(file) {
+                Ok(v) => paths.extend(v),
+                Err(e) => log::debug!("failed to read the recents of {}: {e}", file.display()),
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| path.is_dir() && seen.insert(path.clone()))
+        .map(|path| Project {
+            name: path
+                .file_name()
+                .map_or_else(|| path.to_string_lossy(), |v| v.to_string_lossy())
+                .into(),
+            path: path.into(),
+        })
+        .collect()
+}
+
+impl StructPlugin for ProjectsPlugin {
+    fn prefix() -> &'static str {
+        "project"
+    }
+
+    fn description() -> &'static str {
+        "Open recently used projects in your editor"
+    }
+
+    fn config() -> Option<PluginSettings> {
+        let mut values = HashMap::new();
+        values.insert(
+            "editor".into(),
+            PluginSettings::StringInput {
+                min: 1,
+                max: None,
+                label: Some("The command used to open a project".into()),
+                default: "code".into(),
+            },
+        );
+        Some(PluginSettings::Object {
+            values,
+            label: Some("Projects".into()),
+        })
+    }
+
+    async fn get_for_values(
+        &self,
+        input: &MatcherInput,
+        builder: ResultBuilderRef<'_>,
+        _: PluginContext<'_>,
+    ) {
+        let iter = self
+            .projects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| {
+                input.matches_perfect(&v.name).map(|perfect| {
+                    Entry::new(v.name.clone(), v.path.clone(), CustomData::new(i)).perfect(perfect)
+                })
+            });
+        builder.commit(iter).await;
+    }
+
+    async fn init(&mut self, _: PluginContext<'_>) {
+        let files = project_files();
+        let mut modified = Vec::with_capacity(files.len());
+        for file in &files {
+            modified.push(
+                tokio::fs::metadata(file)
+                    .await
+                    .and_then(|v| v.modified())
+                    .ok(),
+            );
+        }
+        let cached = CACHE
+            .lock()
+            .expect("failed to lock the project cache")
+            .as_ref()
+            .filter(|cache| cache.modified == modified)
+            .map(|cache| cache.projects.clone());
+        if let Some(projects) = cached {
+            self.projects = projects;
+            return;
+        }
+        self.projects = read_projects(&files).await.into();
+        *CACHE.lock().expect("failed to lock the project cache") = Some(Cache {
+            modified,
+            projects: self.projects.clone(),
+        });
+    }
+
+    fn handle_pre(&self, thing: CustomData, action: &str, ctx: PluginContext<'_>) -> Task<Message> {
+        let project = &self.projects[thing.into::<usize>()];
+        if action == "open" {
+            let editor = ctx.config.map_or("code", |v| v["editor"].as_str_default());
+            let mut split = editor.split_whitespace();
+            let Some(program) = split.next() else {
+                log::error!("no editor is configured");
+                return Task::none();
+            };
+            let mut cmd = Command::new(program);
+            cmd.args(split).arg(&*project.path);
+            utils::run_cmd(cmd);
+        } else if let Some(terminal) = &*utils::TERMINAL {
+            let mut cmd = Command::new(terminal);
+            cmd.current_dir(&project.path);
+            utils::run_cmd(cmd);
+        }
+        Task::none()
+    }
+
+    fn actions(&self) -> &'static [Action] {
+        const {
+            &[
+                Action::default("Open in editor", "open"),
+                Action::new(
+                    "Open in terminal",
+                    "terminal",
+                    (Modifiers::CTRL, Key::Named(Named::Enter)),
+                ),
+            ]
+        }
+    }
+}
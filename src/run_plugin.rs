@@ -1,6 +1,13 @@
-use std::{collections::HashSet, path::Path, process::Command, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    ffi::OsStr,
+    path::Path,
+    process::Command,
+    sync::{Arc, Mutex},
+};
 
-use freedesktop_file_parser::EntryType;
+use freedesktop_file_parser::{EntryType, LocaleString};
 use iced::{
     Task,
     advanced::graphics::core::SmolStr,
@@ -18,6 +25,102 @@ struct FileEntry {
     exec: Arc<str>,
     description: Arc<str>,
     path: Arc<Path>,
+    /// the commands of the actions declared in the desktop file (e.g. "New Window")
+    desktop_actions: Vec<Arc<str>>,
+    /// the actions of this entry, empty if the desktop file doesn't declare any
+    actions: Vec<Action>,
+    /// appimages are run directly instead of through the exec line of a desktop file
+    appimage: bool,
+    /// the mime types the application can open, from `MimeType=`
+    mime_types: Vec<Arc<str>>,
+}
+
+impl FileEntry {
+    /// whether the application declares it can open the mime type, either exactly or through a
+    /// wildcard like `image/*`
+    fn opens(&self, mime: &str) -> bool {
+        let group = mime.split_once('/').map_or(mime, |(group, _)| group);
+        self.mime_types
+            .iter()
+            .any(|v| &**v == mime || v.strip_suffix("/*") == Some(group))
+    }
+}
+
+/// the file waiting for an application to be picked by "open with", and its mime type
+static OPEN_WITH: Mutex<Option<(Arc<Path>, String)>> = Mutex::new(None);
+
+/// the entry data of an application offered to open [`OPEN_WITH`]
+#[derive(Clone)]
+struct OpenWith {
+    app: usize,
+    file: Arc<Path>,
+}
+
+/// lets the user pick an application for the file, by listing the ones for its mime type once
+/// the query starts with the prefix of this plugin. the pick is forgotten when the window hides.
+pub fn open_with(file: Arc<Path>, mime: String) {
+    if let Ok(mut pending) = OPEN_WITH.lock() {
+        *pending = Some((file, mime));
+    }
+}
+
+fn clear_open_with() {
+    if let Ok(mut pending) = OPEN_WITH.lock() {
+        *pending = None;
+    }
+}
+
+/// the prefix of the ids of actions declared in desktop files, followed by their index
+const DESKTOP_ACTION_PREFIX: &str = "desktop-action-";
+
+/// the variant of a string for the user's locale, or the unlocalized one if there's none
+fn localized(string: &LocaleString) -> &str {
+    utils::LOCALES
+        .iter()
+        .find_map(|locale| string.variants.get(locale))
+        .unwrap_or(&string.default)
+}
+
+/// removes the field codes, as luma never passes any files or urls, and the markers flatpak puts
+/// around them to forward files into the sandbox.
+fn strip_field_codes(exec: &str) -> String {
+    exec.split_whitespace()
+        .filter(|arg| {
+            !matches!(
+                *arg,
+                "%f" | "%F"
+                    | "%u"
+                    | "%U"
+                    | "%i"
+                    | "%c"
+                    | "%k"
+                    | "%d"
+                    | "%D"
+                    | "%n"
+                    | "%N"
+                    | "%v"
+                    | "%m"
+                    | "@@"
+                    | "@@u"
+                    | "@@f"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn run_exec(exec: &str, terminal: bool) {
+    let mut split = exec.split_whitespace();
+    let Some(command) = split.next() else {
+        return;
+    };
+    let mut command = Command::new(command);
+    command.args(split);
+    if terminal {
+        utils::run_in_terminal(&command);
+    } else {
+        utils::run_cmd(command);
+    }
 }
 
 #[derive(Default)]
@@ -30,12 +133,48 @@ impl StructPlugin for RunPlugin {
         "run"
     }
 
+    fn display_name() -> &'static str {
+        "Applications"
+    }
+
+    fn description() -> &'static str {
+        "Run installed applications"
+    }
+
     async fn get_for_values(
         &self,
         input: &MatcherInput,
         builder: ResultBuilderRef<'_>,
         _: PluginContext<'_>,
     ) {
+        let pending = OPEN_WITH.lock().ok().and_then(|v| v.clone());
+        if let Some((file, mime)) = pending.filter(|_| input.has_prefix()) {
+            // apps that don't list the type can usually still open it, so they're offered when
+            // none do
+            let any_opens = self.files.iter().any(|v| v.opens(&mime));
+            let iter = self
+                .files
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| {
+                    if any_opens {
+                        v.opens(&mime)
+                    } else {
+                        !v.mime_types.is_empty()
+                    }
+                })
+                // the prefix alone lists all of them
+                .filter(|(_, v)| input.is_empty() || input.matches(&v.name))
+                .map(|(app, v)| {
+                    let data = OpenWith {
+                        app,
+                        file: file.clone(),
+                    };
+                    Entry::new(v.name.clone(), v.description.clone(), CustomData::new(data))
+                });
+            builder.commit(iter).await;
+            return;
+        }
         let iter = self
             .files
             .iter()
@@ -48,10 +187,10 @@ impl StructPlugin for RunPlugin {
         builder.commit(iter).await;
     }
 
-    async fn init(&mut self, _: PluginContext<'_>) {
+    async fn init(&mut self, context: PluginContext<'_>) {
         let mut file_entries = Vec::new();
         let mut programs = HashSet::new();
-        for dir in utils::APPLICATION_DIRS.iter() {
+        for dir in context.global_config.application_dirs() {
             let Ok(mut dirent) = tokio::fs::read_dir(dir).await else {
                 continue;
             };
@@ -60,6 +199,9 @@ impl StructPlugin for RunPlugin {
                     continue;
                 };
                 let Some(entry) = entry else { break };
+                if context.is_cancelled() {
+                    return;
+                }
                 let path = entry.path();
                 let Ok(contents) = std::fs::read_to_string(&path) else {
                     continue;
@@ -73,25 +215,31 @@ impl StructPlugin for RunPlugin {
                 let EntryType::Application(application) = parsed.entry.entry_type else {
                     continue;
                 };
-                let name = parsed.entry.name.get_variant("en");
+                let name = localized(&parsed.entry.name);
                 if programs.contains(name) {
                     continue;
                 }
                 programs.insert(name.to_string());
-                let Some(mut exec) = application.exec else {
+                let Some(exec) = application.exec.as_deref().map(strip_field_codes) else {
                     continue;
                 };
-                if let Some(pos) = exec.find("%u") {
-                    exec.replace_range(pos..pos + 2, "");
-                }
-                if let Some(pos) = exec.find("%U") {
-                    exec.replace_range(pos..pos + 2, "");
-                }
-                if let Some(pos) = exec.find("%f") {
-                    exec.replace_range(pos..pos + 2, "");
-                }
-                if let Some(pos) = exec.find("%F") {
-                    exec.replace_range(pos..pos + 2, "");
+                let mut desktop_actions = Vec::new();
+                let mut actions = Vec::new();
+                for id in application.actions.iter().flatten() {
+                    let Some(action) = parsed.actions.get(id) else {
+                        continue;
+                    };
+                    let Some(exec) = action.exec.as_deref().map(strip_field_codes) else {
+                        continue;
+                    };
+                    if actions.is_empty() {
+                        actions.extend_from_slice(self.actions());
+                    }
+                    actions.push(Action::without_shortcut_owned(
+                        localized(&action.name).to_string(),
+                        format!("{DESKTOP_ACTION_PREFIX}{}", desktop_actions.len()),
+                    ));
+                    desktop_actions.push(exec.into());
                 }
                 file_entries.push(FileEntry {
                     name: name.into(),
@@ -100,9 +248,51 @@ impl StructPlugin for RunPlugin {
                     description: parsed
                         .entry
                         .comment
-                        .map(|v| v.get_variant("en").into())
+                        .map(|v| localized(&v).into())
                         .unwrap_or_default(),
                     path: path.into(),
+                    desktop_actions,
+                    actions,
+                    appimage: false,
+                    mime_types: application
+                        .mime_type
+                        .into_iter()
+                        .flatten()
+                        .map(Into::into)
+                        .collect(),
+                });
+            }
+        }
+        for dir in &context.global_config.appimage_dirs {
+            let Ok(mut dirent) = tokio::fs::read_dir(dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = dirent.next_entry().await {
+                let path = entry.path();
+                if !path
+                    .extension()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|v| v.eq_ignore_ascii_case("appimage"))
+                {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(OsStr::to_str) else {
+                    continue;
+                };
+                if !programs.insert(name.to_string()) {
+                    continue;
+                }
+                file_entries.push(FileEntry {
+                    name: name.into(),
+                    terminal: false,
+                    exec: Arc::default(),
+                    description: "AppImage".into(),
+                    path: path.into(),
+                    desktop_actions: Vec::new(),
+                    // there's no desktop entry to open
+                    actions: vec![Action::default("Run Program", "run")],
+                    appimage: true,
+                    mime_types: Vec::new(),
                 });
             }
         }
@@ -115,20 +305,32 @@ impl StructPlugin for RunPlugin {
         action: &str,
         _: PluginContext<'_>,
     ) -> iced::Task<Message> {
-        let file = &self.files[thing.into::<usize>()];
-
-        if action == "run" {
-            let mut split = file.exec.split(' ');
-            let Some(command) = split.next() else {
-                return Task::none();
-            };
-            let mut command = Command::new(command);
-            command.args(split);
-            if file.terminal {
-                utils::run_in_terminal(&command);
+        if let Some(OpenWith { app, file }) = thing.get::<OpenWith>() {
+            clear_open_with();
+            let app = &self.files[*app];
+            if action == "open" {
+                utils::open_file(&*app.path);
             } else {
-                utils::run_cmd(command);
+                utils::with_desktop_file_info(&app.path, |desktop_file| {
+                    utils::run_desktop_file(desktop_file, file);
+                });
             }
+            return Task::none();
+        }
+        let Some(file) = thing.get::<usize>().and_then(|&i| self.files.get(i)) else {
+            return Task::none();
+        };
+
+        if action == "run" && file.appimage {
+            utils::run_cmd(Command::new(&*file.path));
+        } else if action == "run" {
+            run_exec(&file.exec, file.terminal);
+        } else if let Some(exec) = action
+            .strip_prefix(DESKTOP_ACTION_PREFIX)
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| file.desktop_actions.get(index))
+        {
+            run_exec(exec, file.terminal);
         } else {
             utils::open_file(&*file.path);
         }
@@ -147,4 +349,62 @@ impl StructPlugin for RunPlugin {
             ]
         }
     }
+
+    fn on_hide(&self, _: PluginContext<'_>) -> Task<Message> {
+        clear_open_with();
+        Task::none()
+    }
+
+    fn actions_for(&self, data: &CustomData) -> Cow<'_, [Action]> {
+        match data.get::<usize>().and_then(|&i| self.files.get(i)) {
+            Some(file) if !file.actions.is_empty() => Cow::Borrowed(&file.actions),
+            _ => Cow::Borrowed(self.actions()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::Path, sync::Arc};
+
+    use super::{FileEntry, RunPlugin, clear_open_with, open_with};
+    use crate::{Context, filter_service::collect_entries};
+
+    fn app(name: &str, mime_types: &[&str]) -> FileEntry {
+        FileEntry {
+            name: name.into(),
+            terminal: false,
+            exec: name.into(),
+            description: "".into(),
+            path: Path::new(name).into(),
+            desktop_actions: Vec::new(),
+            actions: Vec::new(),
+            appimage: false,
+            mime_types: mime_types.iter().map(|&v| v.into()).collect(),
+        }
+    }
+
+    #[test]
+    fn open_with_lists_apps() {
+        let plugin = RunPlugin {
+            files: vec![
+                app("GIMP", &["image/png"]),
+                app("Image Viewer", &["image/*"]),
+                app("Text Editor", &["text/plain"]),
+            ],
+        };
+        let context = Context::test();
+        let names = |query| {
+            collect_entries(&plugin, query, true, &context)
+                .into_iter()
+                .map(|v| v.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        open_with(Arc::from(Path::new("/tmp/a.png")), "image/png".to_string());
+        let mut apps = names("");
+        apps.sort();
+        assert_eq!(apps, ["GIMP", "Image Viewer"]);
+        assert_eq!(names("gim"), ["GIMP"]);
+        clear_open_with();
+    }
 }
@@ -13,21 +13,23 @@ use iced::{
     },
 };
 
-use crate::{ALLOWED_ACTION_MODIFIERS, Message};
+use crate::{Message, keybind};
 
-pub struct SearchInput<'a>(TextInput<'a, Message>);
+/// the text input and if it is empty
+pub struct SearchInput<'a>(TextInput<'a, Message>, bool);
 
 impl SearchInput<'_> {
-    pub fn new(query: &str, id: Id) -> Self {
+    pub fn new(query: &str, id: Id, text_size: f32) -> Self {
         let inner = iced::widget::text_input("Search", query)
             .id(id)
+            .size(text_size)
             .on_input(Message::UpdateSearch)
             .style(|theme, status| {
                 let mut style = text_input::default(theme, status);
                 style.border = Border::default().width(0.0);
                 style
             });
-        Self(inner)
+        Self(inner, query.is_empty())
     }
 }
 
@@ -105,10 +107,19 @@ impl Widget<Message, Theme, Renderer> for SearchInput<'_> {
                 }) => {
                     shell.publish(Message::HideActions);
                 }
-                Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key,
+                    modifiers,
+                    repeat,
+                    ..
+                }) => {
                     let is_ctrl = *modifiers == Modifiers::CTRL;
                     let is_ctrl_shift = *modifiers == Modifiers::CTRL.union(Modifiers::SHIFT);
                     match key {
+                        // there's nothing left to delete, so the prefix chip is removed
+                        Key::Named(Named::Backspace) if self.1 && modifiers.is_empty() => {
+                            shell.publish(Message::RemovePrefix);
+                        }
                         Key::Named(Named::ArrowLeft | Named::ArrowRight | Named::Backspace)
                             if is_ctrl || is_ctrl_shift =>
                         {
@@ -124,10 +135,20 @@ impl Widget<Message, Theme, Renderer> for SearchInput<'_> {
                             // actions list that shows up when holding down alt.)
                             if (*modifiers | Modifiers::ALT) == Modifiers::ALT =>
                         {
-                            shell.publish(Message::Submit);
+                            shell.publish(Message::Submit(false));
+                        }
+                        // shift+enter runs the action without closing the window
+                        Key::Named(Named::Enter)
+                            if modifiers.difference(Modifiers::ALT) == Modifiers::SHIFT =>
+                        {
+                            shell.publish(Message::Submit(true));
                         }
                         Key::Named(Named::PageUp) => shell.publish(Message::Go10Up),
                         Key::Named(Named::PageDown) => shell.publish(Message::Go10Down),
+                        Key::Named(Named::ArrowUp) if *repeat => shell.publish(Message::HeldGoUp),
+                        Key::Named(Named::ArrowDown) if *repeat => {
+                            shell.publish(Message::HeldGoDown);
+                        }
                         Key::Named(Named::ArrowUp) => shell.publish(Message::GoUp),
                         Key::Named(Named::ArrowDown) => shell.publish(Message::GoDown),
                         Key::Named(Named::Escape) => shell.publish(Message::HideMainWindow),
@@ -135,7 +156,7 @@ impl Widget<Message, Theme, Renderer> for SearchInput<'_> {
                         Key::Named(Named::Tab) => {
                             shell.publish(Message::KeyPressed(Key::Named(Named::Tab), *modifiers));
                         }
-                        _ if ALLOWED_ACTION_MODIFIERS.intersects(*modifiers) => {
+                        _ if keybind::is_shortcut_modifiers(*modifiers) => {
                             shell.publish(Message::KeyPressed(key.clone(), *modifiers));
                         }
                         _ => break 'blk false,
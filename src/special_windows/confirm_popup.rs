@@ -0,0 +1,58 @@
+use iced::{
+    Element, Length, Task,
+    alignment::{Horizontal, Vertical},
+    widget::{button, column, container, row, text, vertical_space},
+    window,
+};
+
+use crate::{Message, config::Config};
+
+/// asks before running a destructive action
+#[derive(Debug)]
+pub struct State {
+    pub(crate) message: String,
+    /// sent when the action is confirmed
+    pub(crate) on_confirm: Message,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ConfirmMessage {
+    Confirm,
+}
+
+impl State {
+    pub fn view(&self, id: window::Id, config: &Config) -> Element<'_, Message> {
+        column![
+            text(&self.message)
+                .width(Length::Fill)
+                .size(config.text_size(16.0))
+                .align_y(Vertical::Center),
+            vertical_space().height(Length::Fill),
+            container(
+                row![
+                    button(text("Confirm").size(config.text_size(16.0))).on_press(
+                        Message::SpecialWindow(
+                            super::SpecialWindowMessage::Confirm(ConfirmMessage::Confirm),
+                            id,
+                        )
+                    ),
+                    button(text("Cancel").size(config.text_size(16.0))).on_press(Message::Hide(id)),
+                ]
+                .spacing(10)
+            )
+            .align_x(Horizontal::Center)
+            .width(Length::Fill),
+        ]
+        .padding(20)
+        .into()
+    }
+
+    pub fn update(&mut self, id: window::Id, message: ConfirmMessage) -> Task<Message> {
+        match message {
+            ConfirmMessage::Confirm => Task::batch([
+                Task::done(Message::Hide(id)),
+                Task::done(std::mem::replace(&mut self.on_confirm, Message::None)),
+            ]),
+        }
+    }
+}
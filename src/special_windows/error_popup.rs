@@ -1,43 +1,68 @@
 use iced::{
     Color, Element, Length,
-    alignment::{Horizontal, Vertical},
-    widget::{button, column, container, row, svg, text, vertical_space},
+    alignment::Horizontal,
+    widget::{button, column, container, row, scrollable, svg, text},
     window,
 };
 
-use crate::Message;
+use crate::{Message, config::Config};
 
 #[derive(Debug)]
 pub struct State {
-    pub(crate) message: String,
+    pub(crate) messages: Vec<String>,
 }
 
 const ERR_ICON: &[u8] = include_bytes!("../../icons/exclamation-circle.svg");
 
 impl State {
-    pub fn view(&self, id: window::Id) -> Element<'_, Message> {
+    pub fn new(message: String) -> Self {
+        Self {
+            messages: vec![message],
+        }
+    }
+
+    pub fn contains(&self, message: &str) -> bool {
+        self.messages.iter().any(|v| v == message)
+    }
+
+    /// adds another error to this popup
+    pub fn push(&mut self, message: String) {
+        if !self.contains(&message) {
+            self.messages.push(message);
+        }
+    }
+
+    pub fn view(&self, id: window::Id, config: &Config) -> Element<'_, Message> {
         let row = row![
             svg(svg::Handle::from_memory(ERR_ICON))
-                .width(Length::Fixed(40.0))
-                .height(Length::Fixed(40.0))
+                .width(Length::Fixed(config.text_size(40.0)))
+                .height(Length::Fixed(config.text_size(40.0)))
                 .style(|_, _| svg::Style {
                     color: Some(Color::from_rgb8(0xfb, 0x2c, 0x36))
                 }),
-            text(&self.message)
-                .width(Length::Fill)
-                .size(16)
-                .height(Length::Fill)
-                .align_y(Vertical::Center)
+            scrollable(
+                text(self.messages.join("\n"))
+                    .width(Length::Fill)
+                    .size(config.text_size(16.0))
+            )
+            .height(Length::Fill)
         ]
         .spacing(10)
-        .height(Length::Shrink);
+        .height(Length::Fill);
         column![
             row,
-            vertical_space().height(Length::Fill),
-            container(button("Ok").on_press(Message::Hide(id)))
+            container(
+                row![
+                    button(text("Copy").size(config.text_size(16.0)))
+                        .on_press(Message::CopyToClipboard(self.messages.join("\n"))),
+                    button(text("Ok").size(config.text_size(16.0))).on_press(Message::Hide(id)),
+                ]
+                .spacing(10)
+            )
                 .align_x(Horizontal::Center)
                 .width(Length::Fill),
         ]
+        .spacing(10)
         .padding(20)
         .into()
     }
@@ -1,8 +1,10 @@
+use confirm_popup::ConfirmMessage;
 use iced::{Element, Size, Task, window};
 use settings::SettingsMessage;
 
-use crate::{Message, State};
+use crate::{Message, State, config::Config};
 
+pub mod confirm_popup;
 pub mod error_popup;
 pub mod settings;
 pub mod warning_popup;
@@ -12,11 +14,13 @@ pub enum SpecialWindowState {
     ErrorPopup(error_popup::State),
     WarnPopup(warning_popup::State),
     Settings(settings::SettingsState),
+    Confirm(confirm_popup::State),
 }
 
 #[derive(Clone, Debug)]
 pub enum SpecialWindowMessage {
     Settings(SettingsMessage),
+    Confirm(ConfirmMessage),
 }
 
 impl Clone for SpecialWindowState {
@@ -28,9 +32,10 @@ impl Clone for SpecialWindowState {
 impl SpecialWindowState {
     pub fn view<'a>(&'a self, id: window::Id, parent_state: &'a State) -> Element<'a, Message> {
         match self {
-            SpecialWindowState::ErrorPopup(state) => state.view(id),
-            SpecialWindowState::WarnPopup(state) => state.view(id),
+            SpecialWindowState::ErrorPopup(state) => state.view(id, &parent_state.context.config),
+            SpecialWindowState::WarnPopup(state) => state.view(id, &parent_state.context.config),
             SpecialWindowState::Settings(state) => state.view(id, parent_state),
+            SpecialWindowState::Confirm(state) => state.view(id, &parent_state.context.config),
         }
     }
 
@@ -44,28 +49,52 @@ impl SpecialWindowState {
             (SpecialWindowState::Settings(state), SpecialWindowMessage::Settings(message)) => {
                 state.update(id, parent_state, message)
             }
+            (SpecialWindowState::Confirm(state), SpecialWindowMessage::Confirm(message)) => {
+                state.update(id, message)
+            }
             _ => Task::none(),
         }
     }
 
     #[allow(clippy::unnecessary_wraps)]
-    pub fn size(&self) -> Option<Size> {
+    pub fn size(&self, config: &Config) -> Option<Size> {
         match self {
-            SpecialWindowState::ErrorPopup(_) | SpecialWindowState::WarnPopup(_) => Some(Size {
-                width: 400.0,
-                height: 150.0,
+            SpecialWindowState::ErrorPopup(_)
+            | SpecialWindowState::WarnPopup(_)
+            | SpecialWindowState::Confirm(_) => Some(Size {
+                width: config.text_size(400.0),
+                height: config.text_size(150.0),
+            }),
+            SpecialWindowState::Settings(_) => Some(Size {
+                width: config.text_size(500.0),
+                height: config.text_size(600.0),
             }),
-            SpecialWindowState::Settings(_) => None,
         }
     }
 
+    /// if this is an error or warning popup
+    pub fn is_popup(&self) -> bool {
+        matches!(
+            self,
+            SpecialWindowState::ErrorPopup(_) | SpecialWindowState::WarnPopup(_)
+        )
+    }
+
     pub fn new_error_popup(message: String) -> Self {
-        Self::ErrorPopup(error_popup::State { message })
+        Self::ErrorPopup(error_popup::State::new(message))
     }
     pub fn new_warning_popup(message: String) -> Self {
         Self::WarnPopup(warning_popup::State { message })
     }
 
+    /// asks if `on_confirm` should be sent
+    pub fn new_confirm_popup(message: String, on_confirm: Message) -> Self {
+        Self::Confirm(confirm_popup::State {
+            message,
+            on_confirm,
+        })
+    }
+
     pub(crate) fn settings(config: crate::config::Config) -> Self {
         Self::Settings(settings::SettingsState::new(config))
     }
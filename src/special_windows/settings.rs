@@ -1,14 +1,14 @@
 use iced::{
     Element, Length, Task,
     alignment::Vertical,
-    widget::{button, checkbox, column, horizontal_space, row, text, vertical_space},
+    widget::{button, checkbox, column, horizontal_space, row, scrollable, text, vertical_space},
     window,
 };
 
 use crate::{
     Message, State,
     config::{BlurAction, Config},
-    plugin::StringLike,
+    plugin::{ALWAYS_ENABLED_PLUGINS, StringLike},
 };
 
 #[derive(Debug)]
@@ -37,15 +37,19 @@ impl SettingsState {
     }
 
     pub fn view<'a>(&self, id: window::Id, state: &'a State) -> Element<'a, Message> {
+        let text_size = state.context.config.text_size(16.0);
         let mut col = column![
-            text("Luma Settings").size(25).width(Length::Fill).center(),
+            text("Luma Settings")
+                .size(state.context.config.text_size(25.0))
+                .width(Length::Fill)
+                .center(),
             vertical_space()
                 .width(Length::Fill)
                 .height(Length::Fixed(10.0))
-        ]
-        .padding(10.0);
+        ];
         col = col.push(
             checkbox("Auto Resize", self.config.auto_resize)
+                .text_size(text_size)
                 .on_toggle(move |v| (SettingsMessage::SetAutoResize(v), id).into()),
         );
         col = col.push(
@@ -53,20 +57,27 @@ impl SettingsState {
                 "Force focus when the launcher is opened",
                 matches!(self.config.on_blur, BlurAction::Refocus),
             )
+            .text_size(text_size)
             .on_toggle(move |v| (SettingsMessage::SetForceFocus(v), id).into()),
         );
-        col = col.push(text("Plugins").size(18).width(Length::Fill).center());
+        col = col.push(
+            text("Plugins")
+                .size(state.context.config.text_size(18.0))
+                .width(Length::Fill)
+                .center(),
+        );
         for plugin in state
             .plugin_builder
             .iter()
             .map(|v| &v.0)
-            .filter(|v| **v != "control")
+            .filter(|v| !ALWAYS_ENABLED_PLUGINS.contains(&v.to_str()))
         {
             let mut row = row![
                 checkbox(
                     plugin.clone(),
                     self.config.enabled_plugins.contains(plugin.to_str()),
                 )
+                .text_size(text_size)
                 .on_toggle(move |v| {
                     (SettingsMessage::SetPluginEnabled(plugin.clone(), v), id).into()
                 }),
@@ -74,18 +85,23 @@ impl SettingsState {
             if state.plugin_configs.contains_key(plugin) {
                 row = row
                     .push(horizontal_space().width(Length::Fixed(20.0)))
-                    .push(button("Edit Plugin Config"))
+                    .push(button(text("Edit Plugin Config").size(text_size)))
                     .align_y(Vertical::Center);
             }
             col = col.push(row);
         }
-        col = col
-            .push(vertical_space().width(Length::Fill).height(Length::Fill))
-            .push(row![
-                button("Save").on_press((SettingsMessage::Save, id).into()),
-                button("Discard").on_press((SettingsMessage::Discard, id).into())
-            ]);
-        col.into()
+        // only the settings scroll, so the buttons stay visible with many plugins
+        column![
+            scrollable(col).height(Length::Fill),
+            row![
+                button(text("Save").size(text_size)).on_press((SettingsMessage::Save, id).into()),
+                button(text("Discard").size(text_size))
+                    .on_press((SettingsMessage::Discard, id).into())
+            ]
+        ]
+        .spacing(10.0)
+        .padding(10.0)
+        .into()
     }
 
     pub fn update(
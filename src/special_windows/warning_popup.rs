@@ -5,7 +5,7 @@ use iced::{
     window,
 };
 
-use crate::Message;
+use crate::{Message, config::Config};
 
 #[derive(Debug)]
 pub struct State {
@@ -15,17 +15,17 @@ pub struct State {
 const WARN_ICON: &[u8] = include_bytes!("../../icons/alert-triangle.svg");
 
 impl State {
-    pub fn view(&self, id: window::Id) -> Element<'_, Message> {
+    pub fn view(&self, id: window::Id, config: &Config) -> Element<'_, Message> {
         let row = row![
             svg(svg::Handle::from_memory(WARN_ICON))
-                .width(Length::Fixed(40.0))
-                .height(Length::Fixed(40.0))
+                .width(Length::Fixed(config.text_size(40.0)))
+                .height(Length::Fixed(config.text_size(40.0)))
                 .style(|_, _| svg::Style {
                     color: Some(Color::from_rgb8(0xfd, 0xc7, 0x00))
                 }),
             text(&self.message)
                 .width(Length::Fill)
-                .size(16)
+                .size(config.text_size(16.0))
                 .height(Length::Fill)
                 .align_y(Vertical::Center),
         ]
@@ -34,7 +34,14 @@ impl State {
         column![
             row,
             vertical_space().height(Length::Fill),
-            container(button("Ok").on_press(Message::Hide(id)))
+            container(
+                row![
+                    button(text("Copy").size(config.text_size(16.0)))
+                        .on_press(Message::CopyToClipboard(self.message.clone())),
+                    button(text("Ok").size(config.text_size(16.0))).on_press(Message::Hide(id)),
+                ]
+                .spacing(10)
+            )
                 .align_x(Horizontal::Center)
                 .width(Length::Fill),
         ]
@@ -1,11 +1,12 @@
-use std::{any::Any, sync::Arc};
+use std::{any::Any, sync::Arc, thread::JoinHandle};
 
 use rusqlite::{Connection, Result, Row, ToSql, params_from_iter};
-use tokio::sync::mpsc::{Sender, UnboundedSender, channel, unbounded_channel};
+use tokio::sync::mpsc::{Sender, UnboundedReceiver, UnboundedSender, channel, unbounded_channel};
 
 use crate::{plugin::StringLike, utils};
 
 type ProcessFunc = dyn Send + FnOnce(&Row<'_>) -> Result<Box<dyn Any + Send>>;
+type ProcessAllFunc = dyn Send + FnMut(&Row<'_>) -> Result<Box<dyn Any + Send>>;
 
 type Params = Box<[Box<dyn ToSql + Send>]>;
 
@@ -16,36 +17,76 @@ enum SqliteRequest {
         process: Box<ProcessFunc>,
         responder: Sender<Result<Box<dyn Any + Send>>>,
     },
+    QueryAll {
+        query: StringLike,
+        params: Params,
+        process: Box<ProcessAllFunc>,
+        responder: Sender<Result<Vec<Box<dyn Any + Send>>>>,
+    },
     Execute {
         query: StringLike,
         params: Params,
         responder: Option<Sender<Result<usize>>>,
     },
+    /// runs all statements or none of them
+    Transaction(Vec<(StringLike, Params)>),
+    /// closes the connection once the requests sent before it are handled
     Shutdown,
 }
 
 #[derive(Clone, Debug)]
 pub struct SqliteContext(Arc<UnboundedSender<SqliteRequest>>);
 
-pub struct SqliteDeinitializer(Arc<UnboundedSender<SqliteRequest>>);
+/// closes the database when dropped, waiting for the queued writes so they aren't lost when the
+/// process exits right after.
+pub struct SqliteDeinitializer(Arc<UnboundedSender<SqliteRequest>>, Option<JoinHandle<()>>);
 impl Drop for SqliteDeinitializer {
     fn drop(&mut self) {
         log::debug!("requesting to close sqlite cache");
         _ = self.0.send(SqliteRequest::Shutdown);
+        if let Some(thread) = self.1.take()
+            && thread.join().is_err()
+        {
+            log::error!("the sqlite cache panicked while closing");
+        }
     }
 }
 
-pub fn init() -> Result<(SqliteContext, SqliteDeinitializer)> {
+/// `wal` switches the database to a write-ahead log, which makes writes faster but keeps two
+/// more files next to it
+pub fn init(wal: bool) -> Result<(SqliteContext, SqliteDeinitializer)> {
     let connection = Connection::open(utils::DATA_DIR.join("cache.sqlite"))?;
+    // the journal mode is stored in the database, so it's set back when wal gets disabled
+    let mode = if wal { "wal" } else { "delete" };
+    let set_mode: String =
+        connection.pragma_update_and_check(None, "journal_mode", mode, |row| row.get(0))?;
+    if !set_mode.eq_ignore_ascii_case(mode) {
+        log::warn!("failed to set the journal mode of the sqlite cache to {mode}, it's {set_mode}");
+    }
+    let (sender, thread) = spawn(connection);
+    Ok((
+        SqliteContext(sender.clone()),
+        SqliteDeinitializer(sender, Some(thread)),
+    ))
+}
+
+/// a database that only lives in memory, for tests. it's closed once the context and all of its
+/// clones are dropped.
+#[cfg(test)]
+pub fn init_in_memory() -> SqliteContext {
+    let connection = Connection::open_in_memory().expect("an in-memory database can be opened");
+    SqliteContext(spawn(connection).0)
+}
+
+fn spawn(connection: Connection) -> (Arc<UnboundedSender<SqliteRequest>>, JoinHandle<()>) {
     let (sender, mut receiver) = unbounded_channel();
     let sender = Arc::new(sender);
-    std::thread::spawn(move || {
+    let thread = std::thread::spawn(move || {
         log::debug!("initialized sqlite cache");
-        loop {
-            let Some(request) = receiver.blocking_recv() else {
-                log::debug!("closing sqlite cache");
-                return;
-            };
+        // a request that was received while writes were batched
+        let mut next = None;
+        // stops once all senders are gone or the channel was closed and drained
+        while let Some(request) = next.take().or_else(|| receiver.blocking_recv()) {
             match request {
                 SqliteRequest::Query {
                     query,
@@ -60,6 +101,27 @@ pub fn init() -> Result<(SqliteContext, SqliteDeinitializer)> {
                     // gets logged.
                     _ = responder.try_send(result);
                 }
+                SqliteRequest::QueryAll {
+                    query,
+                    params,
+                    process,
+                    responder,
+                } => {
+                    let result = connection.prepare(&query).and_then(|mut statement| {
+                        statement
+                            .query_map(params_from_iter(params.iter()), process)?
+                            .collect()
+                    });
+                    // if the channel is closed, the recipient probably doesn't care
+                    // anymore, which is why nothing goes wrong in that case, so nothing
+                    // gets logged.
+                    _ = responder.try_send(result);
+                }
+                SqliteRequest::Execute {
+                    query,
+                    params,
+                    responder: None,
+                } => next = execute_queued(&connection, &mut receiver, query, params),
                 SqliteRequest::Execute {
                     query,
                     params,
@@ -73,14 +135,65 @@ pub fn init() -> Result<(SqliteContext, SqliteDeinitializer)> {
                         _ = responder.try_send(result);
                     }
                 }
+                SqliteRequest::Transaction(statements) => {
+                    if let Err(e) = transaction(&connection, statements) {
+                        log::debug!("a sqlite transaction failed: {e}");
+                    }
+                }
                 SqliteRequest::Shutdown => {
-                    _ = connection.close();
-                    return;
+                    // requests can still be in the channel, e.g. when they were sent from
+                    // another thread at the same time. they're handled before the connection
+                    // is closed, but new ones aren't accepted anymore.
+                    receiver.close();
                 }
             }
         }
+        log::debug!("closing sqlite cache");
+        _ = connection.close();
     });
-    Ok((SqliteContext(sender.clone()), SqliteDeinitializer(sender)))
+    (sender, thread)
+}
+
+/// runs the write along with the ones queued after it in a single transaction, as every
+/// transaction has to wait for the disk. returns the first other request that was received.
+fn execute_queued(
+    connection: &Connection,
+    receiver: &mut UnboundedReceiver<SqliteRequest>,
+    query: StringLike,
+    params: Params,
+) -> Option<SqliteRequest> {
+    let transaction = connection.unchecked_transaction();
+    // writes that nobody waits for don't report their errors
+    _ = connection.execute(&query, params_from_iter(params.iter()));
+    let mut next = None;
+    while let Ok(request) = receiver.try_recv() {
+        match request {
+            SqliteRequest::Execute {
+                query,
+                params,
+                responder: None,
+            } => _ = connection.execute(&query, params_from_iter(params.iter())),
+            request => {
+                next = Some(request);
+                break;
+            }
+        }
+    }
+    if let Ok(transaction) = transaction
+        && let Err(e) = transaction.commit()
+    {
+        log::error!("failed to write to the sqlite cache: {e}");
+    }
+    next
+}
+
+fn transaction(connection: &Connection, statements: Vec<(StringLike, Params)>) -> Result<()> {
+    let transaction = connection.unchecked_transaction()?;
+    for (query, params) in statements {
+        transaction.execute(&query, params_from_iter(params.iter()))?;
+    }
+    // the transaction is rolled back when it's dropped without committing
+    transaction.commit()
 }
 
 pub fn execute(
@@ -98,6 +211,14 @@ pub fn execute(
         .expect("async-sqlite closed");
 }
 
+/// runs the statements in a single transaction, so either all or none of them are applied
+pub fn execute_all(context: &SqliteContext, statements: Vec<(StringLike, Params)>) {
+    context
+        .0
+        .send(SqliteRequest::Transaction(statements))
+        .expect("async-sqlite closed");
+}
+
 /// returns the number of rows changed
 pub async fn await_execute(
     context: &SqliteContext,
@@ -143,3 +264,73 @@ pub async fn await_query<T: Send + 'static, F: Send + 'static + FnOnce(&Row<'_>)
         .unwrap_or(Err(rusqlite::Error::QueryReturnedNoRows))?;
     Ok(*v.downcast().expect("these types *should always* match"))
 }
+
+/// like [`await_query`], but returns all rows instead of just the first one.
+pub async fn await_query_all<
+    T: Send + 'static,
+    F: Send + 'static + FnMut(&Row<'_>) -> Result<T>,
+>(
+    context: &SqliteContext,
+    query: impl Into<StringLike>,
+    params: Box<[Box<dyn ToSql + Send>]>,
+    mut f: F,
+) -> Result<Vec<T>> {
+    // if async-sqlite was closed, the application is about to exit anyway.
+    let (sender, mut receiver) = channel(1);
+    context
+        .0
+        .send(SqliteRequest::QueryAll {
+            query: query.into(),
+            params,
+            process: Box::new(move |row| Ok(Box::new(f(row)?))),
+            responder: sender,
+        })
+        .expect("async-sqlite closed");
+    let rows = receiver
+        .recv()
+        .await
+        .unwrap_or(Err(rusqlite::Error::QueryReturnedNoRows))?;
+    Ok(rows
+        .into_iter()
+        .map(|v| *v.downcast().expect("these types *should always* match"))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{await_execute, await_query, execute, execute_all, init_in_memory};
+
+    #[test]
+    fn writes() {
+        let context = init_in_memory();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("a runtime can be built");
+        let count = runtime.block_on(async {
+            await_execute(&context, "CREATE TABLE numbers(n INTEGER)", [].into())
+                .await
+                .unwrap();
+            // these are batched into a single transaction
+            for n in 0..100 {
+                execute(
+                    &context,
+                    "INSERT INTO numbers (n) VALUES (?1)",
+                    [Box::new(n) as Box<_>].into(),
+                );
+            }
+            // the second statement fails, so the first one is rolled back
+            execute_all(
+                &context,
+                vec![
+                    ("INSERT INTO numbers (n) VALUES (100)".into(), [].into()),
+                    ("INSERT INTO missing (n) VALUES (100)".into(), [].into()),
+                ],
+            );
+            await_query(&context, "SELECT COUNT(*) FROM numbers", [].into(), |row| {
+                row.get::<_, i64>(0)
+            })
+            .await
+        });
+        assert_eq!(count, Ok(100));
+    }
+}
@@ -4,7 +4,7 @@ use iced::{Task, Theme};
 
 use crate::{
     Action, CustomData, Entry, Message, PluginContext, ResultBuilderRef,
-    matcher::MatcherInput,
+    matcher::{MatchMode, MatcherInput},
     plugin::{StringLike, StructPlugin},
 };
 
@@ -23,19 +23,26 @@ impl StructPlugin for ThemePlugin {
         "theme"
     }
 
+    fn description() -> &'static str {
+        "Change the theme of the launcher"
+    }
+
     async fn get_for_values(
         &self,
         input: &MatcherInput,
         builder: ResultBuilderRef<'_>,
         _: PluginContext<'_>,
     ) {
-        let iter = THEMES.iter().filter(|&v| input.matches(&v.0)).map(|v| {
-            Entry::new(
-                v.0.clone(),
-                StringLike::Empty,
-                CustomData::new::<Theme>(v.1.clone()),
-            )
-        });
+        let iter = THEMES
+            .iter()
+            .filter(|&v| input.matches_with(&v.0, MatchMode::Prefix))
+            .map(|v| {
+                Entry::new(
+                    v.0.clone(),
+                    StringLike::Empty,
+                    CustomData::new::<Theme>(v.1.clone()),
+                )
+            });
         builder.commit(iter).await;
     }
 
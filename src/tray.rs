@@ -0,0 +1,70 @@
+use tray_icon::{
+    Icon, TrayIconBuilder,
+    menu::{Menu, MenuEvent, MenuItem},
+};
+
+use crate::{Message, MessageSender, utils::CRATE_NAME};
+
+const ICON_SIZE: u32 = 32;
+
+/// shows the tray icon. it runs on its own thread, as it needs a gtk event loop.
+pub fn spawn(sender: MessageSender) {
+    std::thread::spawn(move || {
+        if let Err(e) = gtk::init() {
+            log::error!("failed to show the tray icon: {e}");
+            return;
+        }
+        let show = MenuItem::new("Show", true, None);
+        let settings = MenuItem::new("Settings", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+        let menu = Menu::new();
+        if let Err(e) = menu.append_items(&[&show, &settings, &quit]) {
+            log::error!("failed to show the tray icon: {e}");
+            return;
+        }
+        let tray = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip(CRATE_NAME)
+            .with_icon(icon())
+            .build();
+        let tray = match tray {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("failed to show the tray icon: {e}");
+                return;
+            }
+        };
+        let (show, settings, quit) = (show.id().clone(), settings.id().clone(), quit.id().clone());
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let message = if event.id == show {
+                Message::Show
+            } else if event.id == settings {
+                Message::OpenSettings
+            } else if event.id == quit {
+                Message::Exit
+            } else {
+                return;
+            };
+            sender.try_send(message);
+        }));
+        gtk::main();
+        drop(tray);
+    });
+}
+
+/// a filled circle, as there's no icon to load
+#[allow(clippy::cast_sign_loss)]
+fn icon() -> Icon {
+    let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+    let radius = ICON_SIZE as f32 / 2.0;
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let distance = (x as f32 - center).hypot(y as f32 - center);
+            // the edge is blended over a pixel to smooth it
+            let alpha = (radius - distance).clamp(0.0, 1.0);
+            rgba.extend([0xbd, 0x93, 0xf9, (alpha * 255.0) as u8]);
+        }
+    }
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).expect("the icon has the right size")
+}
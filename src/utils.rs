@@ -1,5 +1,6 @@
 use std::{
     ffi::OsStr,
+    fmt::Write,
     iter::Iterator,
     path::{Path, PathBuf},
     process::{Command, Stdio},
@@ -13,15 +14,61 @@ use crate::cache::Cache;
 
 pub static CRATE_NAME: &str = env!("CARGO_PKG_NAME");
 pub static CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
-pub static HOME_DIR: LazyLock<PathBuf> =
-    LazyLock::new(|| std::env::home_dir().expect("no homedir was found!"));
+/// the home directory, if there is one (e.g. not in some containers)
+pub static HOME_DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    std::env::home_dir()
+        .filter(|v| !v.as_os_str().is_empty())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|v| !v.is_empty())
+                .map(Into::into)
+        })
+});
+
+/// where the config and data are kept if there's no home directory, so luma still starts. it's
+/// per user, so users sharing the temp directory don't share their files.
+static NO_HOME_DIR: LazyLock<PathBuf> =
+    LazyLock::new(|| std::env::temp_dir().join(format!("{CRATE_NAME}-{}", user_id())));
+
+#[cfg(unix)]
+fn user_id() -> u32 {
+    // SAFETY: getuid always succeeds and has no preconditions
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+fn user_id() -> u32 {
+    0
+}
+
+/// the home directory, or [`NO_HOME_DIR`] if there is none
+fn home_or_fallback() -> &'static Path {
+    HOME_DIR.as_deref().unwrap_or(&NO_HOME_DIR)
+}
+
+/// warns when there's no home directory. this can't happen when [`DATA_DIR`] is first used, as the
+/// logger needs it to find the log file.
+pub fn warn_if_no_home_dir() {
+    if HOME_DIR.is_none() {
+        log::warn!(
+            "no home directory was found, keeping the config and data in {}",
+            NO_HOME_DIR.display()
+        );
+    }
+}
 pub static APPLICATION_DIRS: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
     let mut dirs = vec![PathBuf::from("/usr/share/applications")];
-    let mut application_path = HOME_DIR.clone();
-    application_path.push(".local");
-    application_path.push("share");
-    application_path.push("applications");
-    dirs.push(application_path);
+    if let Some(home) = &*HOME_DIR {
+        let mut application_path = home.clone();
+        application_path.push(".local");
+        application_path.push("share");
+        application_path.push("applications");
+        dirs.push(application_path);
+        // flatpak exports the desktop files of installed apps here, but doesn't always add these
+        // to $XDG_DATA_DIRS
+        dirs.push(home.join(".local/share/flatpak/exports/share/applications"));
+    }
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share/applications"));
     if let Some(data_dir_var) = std::env::var_os("XDG_DATA_DIRS") {
         std::env::split_paths(&data_dir_var)
             .filter_map(|v| v.canonicalize().ok())
@@ -62,6 +109,44 @@ pub static TERMINAL: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
     None
 });
 
+/// the locales localized strings of desktop files are looked up with, most specific first, as
+/// described in the desktop entry spec. `lang_COUNTRY.ENCODING@MODIFIER` results in
+/// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER` and `lang`.
+pub static LOCALES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|v| !v.is_empty())
+    else {
+        return Vec::new();
+    };
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale.as_str(), None),
+    };
+    let locale = locale.split('.').next().unwrap_or_default();
+    // the default strings are used for these
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+    let (lang, country) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+    let mut locales = Vec::with_capacity(4);
+    if let Some(country) = country {
+        if let Some(modifier) = modifier {
+            locales.push(format!("{lang}_{country}@{modifier}"));
+        }
+        locales.push(format!("{lang}_{country}"));
+    }
+    if let Some(modifier) = modifier {
+        locales.push(format!("{lang}@{modifier}"));
+    }
+    locales.push(lang.to_string());
+    locales
+});
+
 pub fn lookup_executable(executable: &OsStr) -> Option<PathBuf> {
     EXECUTABLE_PATHS
         .iter()
@@ -110,27 +195,48 @@ pub fn run_in_terminal(cmd: &Command) {
     }
 }
 
+/// encodes the text for the query of a url. everything but letters, digits and `-_.~` is
+/// escaped.
+pub fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
 pub fn open_link(file: impl AsRef<OsStr>) {
     let mut cmd = Command::new("xdg-open");
     cmd.arg(file);
     run_cmd(cmd);
 }
+
+/// the mime type of a file, as determined by `xdg-mime`. this blocks until it's done.
+pub fn mime_type(file: &Path) -> Option<String> {
+    let output = Command::new("xdg-mime")
+        .arg("query")
+        .arg("filetype")
+        .arg(file)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.lines().next()?.to_string())
+}
+
 pub fn open_file(file: impl Into<Arc<Path>>) {
     let file = file.into();
     log::debug!("opening {}", file.display());
-    let mut cmd = Command::new("xdg-mime");
-    cmd.arg("query").arg("filetype").arg(&*file);
     std::thread::spawn(move || {
-        let output = match cmd.output() {
-            Ok(output) if output.status.success() => output.stdout,
-            _ => b"text/plain".into(),
-        };
-        let Ok(output) = str::from_utf8(&output) else {
-            return;
-        };
-        let output = output.lines().next().unwrap_or_default();
-        cmd = Command::new("xdg-mime");
-        cmd.arg("query").arg("default").arg(output);
+        let mime = mime_type(&file);
+        let mut cmd = Command::new("xdg-mime");
+        cmd.arg("query")
+            .arg("default")
+            .arg(mime.as_deref().unwrap_or("text/plain"));
         let output = match cmd.output() {
             Ok(output) if output.status.success() => output.stdout,
             _ => return,
@@ -236,7 +342,7 @@ pub static CONFIG_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
     let mut buf = if let Some(value) = std::env::var_os("XDG_CONFIG_HOME") {
         PathBuf::from(value)
     } else {
-        let mut buf = HOME_DIR.clone();
+        let mut buf = home_or_fallback().to_path_buf();
         buf.push(".config");
         buf
     };
@@ -248,7 +354,7 @@ pub static DATA_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
     let mut buf = if let Some(value) = std::env::var_os("XDG_DATA_HOME") {
         PathBuf::from(value)
     } else {
-        let mut buf = HOME_DIR.clone();
+        let mut buf = home_or_fallback().to_path_buf();
         buf.push(".local");
         buf.push("share");
         buf
@@ -257,4 +363,85 @@ pub static DATA_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
     buf
 });
 
-pub static CONFIG_FILE: LazyLock<PathBuf> = LazyLock::new(|| CONFIG_DIR.join("config.toml"));
+/// `config.toml`, or `config.json` if only that one exists
+pub static CONFIG_FILE: LazyLock<PathBuf> = LazyLock::new(|| {
+    let toml = CONFIG_DIR.join("config.toml");
+    let json = CONFIG_DIR.join("config.json");
+    if !toml.exists() && json.exists() {
+        json
+    } else {
+        toml
+    }
+});
+
+/// shortens `s` to at most `max_len` bytes without splitting a character. returns if anything
+/// was removed.
+pub fn truncate_at_char_boundary(s: &mut String, max_len: usize) -> bool {
+    if s.len() <= max_len {
+        return false;
+    }
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.truncate(end);
+    true
+}
+
+/// if the config file is json rather than toml
+pub fn config_is_json() -> bool {
+    CONFIG_FILE.extension().is_some_and(|v| v == "json")
+}
+
+pub static AUTOSTART_FILE: LazyLock<PathBuf> = LazyLock::new(|| {
+    let mut buf = CONFIG_DIR
+        .parent()
+        .map_or_else(|| home_or_fallback().join(".config"), Path::to_path_buf);
+    buf.push("autostart");
+    buf.push(format!("{CRATE_NAME}.desktop"));
+    buf
+});
+
+/// installs or removes the xdg autostart entry.
+pub fn set_autostart(enabled: bool) {
+    if !enabled {
+        if AUTOSTART_FILE.exists()
+            && let Err(e) = std::fs::remove_file(&*AUTOSTART_FILE)
+        {
+            log::error!(
+                "Failed to remove the autostart entry {}: {e}",
+                AUTOSTART_FILE.display()
+            );
+        }
+        return;
+    }
+    let exe = match std::env::current_exe() {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("Failed to install the autostart entry: {e}");
+            return;
+        }
+    };
+    let exec = format!("Exec=\"{}\" --daemonize", exe.display());
+    // an existing entry is kept unless it points to another executable, e.g. after luma moved
+    if let Ok(existing) = std::fs::read_to_string(&*AUTOSTART_FILE)
+        && existing.lines().any(|line| line == exec)
+    {
+        return;
+    }
+    let contents = format!(
+        "[Desktop Entry]\nType=Application\nName={CRATE_NAME}\nComment=luma unifies minor actions\n{exec}\nNoDisplay=true\nX-GNOME-Autostart-enabled=true\n"
+    );
+    if let Some(parent) = AUTOSTART_FILE.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        log::error!("Failed to create the path {}: {e}", parent.display());
+        return;
+    }
+    if let Err(e) = std::fs::write(&*AUTOSTART_FILE, contents) {
+        log::error!(
+            "Failed to install the autostart entry {}: {e}",
+            AUTOSTART_FILE.display()
+        );
+    }
+}